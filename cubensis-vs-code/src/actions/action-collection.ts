@@ -3,6 +3,7 @@ import { CubensisRpcClient } from "../client/cubensis-rpc-client";
 import { PluginConfiguration } from "../configuration/plugin-configuration";
 import { PluginActionBase } from "./plugin-action-base";
 import { SetProjectWithHotReloadAction } from "./set-project-action";
+import { ScreenshotAction, ToggleRecordingAction } from "./capture-actions";
 
 export class ActionCollection {
   client: CubensisRpcClient;
@@ -18,6 +19,8 @@ export class ActionCollection {
     this.client = configuration.createClient();
     this.actions = [
       new SetProjectWithHotReloadAction(configuration, this.client),
+      new ScreenshotAction(configuration, this.client),
+      new ToggleRecordingAction(configuration, this.client),
     ];
   }
 }
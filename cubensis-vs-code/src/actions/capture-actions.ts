@@ -0,0 +1,40 @@
+import { PluginActionBase } from "./plugin-action-base";
+import * as vscode from "vscode";
+import {
+  PluginConfiguration,
+  PLUGIN_NAME,
+} from "../configuration/plugin-configuration";
+import { CubensisRpcClient } from "../client/cubensis-rpc-client";
+import { CaptureOptions } from "../client/cubensis-rpc-types";
+
+export class ScreenshotAction extends PluginActionBase {
+  createCommand(): vscode.Disposable {
+    return vscode.commands.registerCommand(
+      `${PLUGIN_NAME}.${this.name}`,
+      () => this.execute(),
+      this
+    );
+  }
+  execute(): void {
+    this.client.screenshot(this.configuration.getCaptureOptions());
+  }
+  constructor(configuration: PluginConfiguration, client: CubensisRpcClient) {
+    super("Screenshot", configuration, client);
+  }
+}
+
+export class ToggleRecordingAction extends PluginActionBase {
+  createCommand(): vscode.Disposable {
+    return vscode.commands.registerCommand(
+      `${PLUGIN_NAME}.${this.name}`,
+      () => this.execute(),
+      this
+    );
+  }
+  execute(): void {
+    this.client.toggleRecording(this.configuration.getCaptureOptions());
+  }
+  constructor(configuration: PluginConfiguration, client: CubensisRpcClient) {
+    super("ToggleRecording", configuration, client);
+  }
+}
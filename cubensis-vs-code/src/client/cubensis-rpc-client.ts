@@ -2,11 +2,21 @@ import { WebSocket } from "ws";
 import { PluginConfiguration } from "../configuration/plugin-configuration";
 import * as vscode from "vscode";
 
-import { RpcResponse, SetProjectRequest } from "./cubensis-rpc-types";
+import {
+  CaptureOptions,
+  RpcResponse,
+  ScreenshotRequest,
+  SetProjectRequest,
+  ShaderDiagnosticsNotification,
+  ToggleRecordingRequest,
+} from "./cubensis-rpc-types";
 
 export class CubensisRpcClient {
   ws: WebSocket;
+  diagnostics: vscode.DiagnosticCollection;
   constructor(configuration: PluginConfiguration) {
+    this.diagnostics = vscode.languages.createDiagnosticCollection("cubensis");
+    let diagnostics = this.diagnostics;
     this.ws = new WebSocket(`ws://${configuration.getAddress()}/socket`, {
       port: configuration.port,
     });
@@ -17,8 +27,16 @@ export class CubensisRpcClient {
     });
     this.ws.on("close", function close() {
       vscode.window.showWarningMessage("Connection to Cubensis lost");
+      diagnostics.clear();
     });
     this.ws.on("message", function message(data) {
+      let notification = ShaderDiagnosticsNotification.tryParse(
+        data.toString()
+      );
+      if (notification !== undefined) {
+        notification.display(diagnostics);
+        return;
+      }
       let response = RpcResponse.parse(data.toString());
       response.display();
     });
@@ -29,4 +47,16 @@ export class CubensisRpcClient {
       this.ws.send(request.serialize());
     }
   }
+  screenshot(options: CaptureOptions) {
+    if (this.ws.OPEN) {
+      let request = new ScreenshotRequest(options);
+      this.ws.send(request.serialize());
+    }
+  }
+  toggleRecording(options: CaptureOptions) {
+    if (this.ws.OPEN) {
+      let request = new ToggleRecordingRequest(options);
+      this.ws.send(request.serialize());
+    }
+  }
 }
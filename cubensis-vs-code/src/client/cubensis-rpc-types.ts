@@ -20,6 +20,41 @@ export class SetProjectRequest extends RpcRequest {
   }
 }
 
+export class CaptureOptions {
+  public scale: number;
+  public include_gui: boolean;
+  constructor(scale: number, includeGui: boolean) {
+    this.scale = scale;
+    this.include_gui = includeGui;
+  }
+}
+
+export class ScreenshotRequest extends RpcRequest {
+  public scale: number;
+  public include_gui: boolean;
+  constructor(options: CaptureOptions) {
+    super();
+    this.scale = options.scale;
+    this.include_gui = options.include_gui;
+  }
+  requestKind(): string {
+    return "Screenshot";
+  }
+}
+
+export class ToggleRecordingRequest extends RpcRequest {
+  public scale: number;
+  public include_gui: boolean;
+  constructor(options: CaptureOptions) {
+    super();
+    this.scale = options.scale;
+    this.include_gui = options.include_gui;
+  }
+  requestKind(): string {
+    return "ToggleRecording";
+  }
+}
+
 export enum RpcSeverity {
   None = 0,
   Info = 1,
@@ -61,3 +96,83 @@ export class RpcResponse {
     }
   }
 }
+
+export class ShaderDiagnostic {
+  public shader: string;
+  public file: string;
+  public path: string;
+  public line: number;
+  public column: number;
+  public message: string;
+  public snippet: string;
+  constructor(
+    shader: string,
+    file: string,
+    path: string,
+    line: number,
+    column: number,
+    message: string,
+    snippet: string
+  ) {
+    this.shader = shader;
+    this.file = file;
+    this.path = path;
+    this.line = line;
+    this.column = column;
+    this.message = message;
+    this.snippet = snippet;
+  }
+  toDiagnostic(): vscode.Diagnostic {
+    // lines and columns are 1-based, 0 if the error has no location
+    let line = Math.max(this.line - 1, 0);
+    let column = Math.max(this.column - 1, 0);
+    let range = new vscode.Range(line, column, line, Number.MAX_SAFE_INTEGER);
+    let diagnostic = new vscode.Diagnostic(
+      range,
+      this.message,
+      vscode.DiagnosticSeverity.Error
+    );
+    diagnostic.source = "cubensis";
+    return diagnostic;
+  }
+}
+
+export class ShaderDiagnosticsNotification {
+  public diagnostics: ShaderDiagnostic[];
+  constructor(diagnostics: ShaderDiagnostic[]) {
+    this.diagnostics = diagnostics;
+  }
+  // notifications are pushed without a request, as {"ShaderDiagnostics":[...]}
+  static tryParse(json: string): ShaderDiagnosticsNotification | undefined {
+    let obj = JSON.parse(json);
+    if (!Array.isArray(obj?.ShaderDiagnostics)) {
+      return undefined;
+    }
+    return new ShaderDiagnosticsNotification(
+      obj.ShaderDiagnostics.map(
+        (d: ShaderDiagnostic) =>
+          new ShaderDiagnostic(
+            d.shader,
+            d.file,
+            d.path,
+            d.line,
+            d.column,
+            d.message,
+            d.snippet
+          )
+      )
+    );
+  }
+  display(collection: vscode.DiagnosticCollection): void {
+    collection.clear();
+    let byPath = new Map<string, vscode.Diagnostic[]>();
+    this.diagnostics.forEach((d) => {
+      let diagnostics = byPath.get(d.path) ?? [];
+      diagnostics.push(d.toDiagnostic());
+      byPath.set(d.path, diagnostics);
+    });
+    byPath.forEach((diagnostics, path) => {
+      collection.set(vscode.Uri.file(path), diagnostics);
+    });
+  }
+}
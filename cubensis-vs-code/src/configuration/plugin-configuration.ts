@@ -1,10 +1,13 @@
 import * as vscode from "vscode";
 import { CubensisRpcClient } from "../client/cubensis-rpc-client";
+import { CaptureOptions } from "../client/cubensis-rpc-types";
 export const PLUGIN_NAME: string = "cubensis-vs-code";
 
 export class PluginConfiguration {
   host: string;
   port: number;
+  captureScale: number;
+  captureIncludeGui: boolean;
   constructor() {
     this.host =
       vscode.workspace
@@ -14,6 +17,17 @@ export class PluginConfiguration {
       vscode.workspace
         .getConfiguration(PLUGIN_NAME)
         .get<number | undefined>("port") ?? 3751;
+    this.captureScale =
+      vscode.workspace
+        .getConfiguration(PLUGIN_NAME)
+        .get<number | undefined>("captureScale") ?? 1.0;
+    this.captureIncludeGui =
+      vscode.workspace
+        .getConfiguration(PLUGIN_NAME)
+        .get<boolean | undefined>("captureIncludeGui") ?? false;
+  }
+  getCaptureOptions(): CaptureOptions {
+    return new CaptureOptions(this.captureScale, this.captureIncludeGui);
   }
   getAddress(): string {
     return `${this.host}:${this.port}`;
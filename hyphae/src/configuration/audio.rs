@@ -1,16 +1,28 @@
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct AudioConfiguration {
     buffer_size: usize,
+    ///Analyze this WAV file instead of a live input stream
+    #[serde(default)]
+    input_file: Option<std::path::PathBuf>,
 }
 
 impl AudioConfiguration {
     pub fn get_buffer_size(&self) -> usize {
         self.buffer_size
     }
+    pub fn get_input_file(&self) -> Option<&std::path::PathBuf> {
+        self.input_file.as_ref()
+    }
+    pub fn set_input_file(&mut self, input_file: Option<std::path::PathBuf>) {
+        self.input_file = input_file;
+    }
 }
 
 impl Default for AudioConfiguration {
     fn default() -> Self {
-        Self { buffer_size: 4096 }
+        Self {
+            buffer_size: 4096,
+            input_file: None,
+        }
     }
 }
@@ -2,15 +2,115 @@
 pub struct GraphicsConfiguration {
     pub enable_vsync: bool,
     pub prefer_legacy_backends: bool,
+    ///Only use software adapters (e.g. llvmpipe/lavapipe) when rendering headless
+    #[serde(default)]
+    pub force_software_adapter: bool,
+    ///Multisample count of mesh render passes (1 or 4), scenes can override it
+    #[serde(default = "GraphicsConfiguration::default_sample_count")]
+    pub sample_count: u32,
+    ///Format of the rendered frame and render history before tonemapping
+    #[serde(default)]
+    pub internal_format: InternalFormat,
+    #[serde(default)]
+    pub tonemapping: Tonemapping,
+    ///Size of the rendered frame relative to the window, scenes can override it
+    #[serde(default)]
+    pub render_resolution: RenderResolution,
+    ///Filter scaling the rendered frame to the window
+    #[serde(default)]
+    pub presentation_filter: PresentationFilter,
 }
 
-impl GraphicsConfiguration {}
+impl GraphicsConfiguration {
+    fn default_sample_count() -> u32 {
+        1
+    }
+}
 
 impl Default for GraphicsConfiguration {
     fn default() -> Self {
         Self {
             enable_vsync: false,
             prefer_legacy_backends: false,
+            force_software_adapter: false,
+            sample_count: Self::default_sample_count(),
+            internal_format: InternalFormat::default(),
+            tonemapping: Tonemapping::default(),
+            render_resolution: RenderResolution::default(),
+            presentation_filter: PresentationFilter::default(),
         }
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+pub enum InternalFormat {
+    ///Same format as the surface, usually 8 bit sRGB
+    #[default]
+    Surface,
+    Rgba16Float,
+    Rgba32Float,
+}
+
+///Mapping of the rendered frame to the surface, applied by the presentation pass
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Tonemapping {
+    pub operator: TonemapOperator,
+    ///Linear factor applied before the operator
+    pub exposure: f32,
+}
+
+impl Default for Tonemapping {
+    fn default() -> Self {
+        Self {
+            operator: TonemapOperator::None,
+            exposure: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+pub enum TonemapOperator {
+    ///Clips values beyond 1
+    #[default]
+    None,
+    Reinhard,
+    ///Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub enum RenderResolution {
+    ///Factor applied to the window size
+    Scale(f32),
+    ///Fixed size regardless of the window, stretched to fit it
+    Fixed { width: u32, height: u32 },
+}
+
+impl Default for RenderResolution {
+    fn default() -> Self {
+        RenderResolution::Scale(1.0)
+    }
+}
+
+impl RenderResolution {
+    ///Render size for a window of `width` by `height`, at least one pixel in each dimension
+    pub fn resolve(&self, width: u32, height: u32) -> [u32; 2] {
+        let [width, height] = match *self {
+            RenderResolution::Scale(scale) => [
+                (width as f32 * scale).round() as u32,
+                (height as f32 * scale).round() as u32,
+            ],
+            RenderResolution::Fixed { width, height } => [width, height],
+        };
+        [width.max(1), height.max(1)]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+pub enum PresentationFilter {
+    Nearest,
+    ///Not available for `Rgba32Float` internal formats, which fall back to `Nearest`
+    #[default]
+    Linear,
+}
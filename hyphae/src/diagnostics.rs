@@ -0,0 +1,68 @@
+use crate::configuration::library::LibraryConfiguration;
+
+///A shader build error, located in the file it comes from
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub struct ShaderDiagnostic {
+    ///Library path of the shader that failed to build
+    pub shader: String,
+    ///Library path of the file containing the error, the shader itself or one of its includes
+    pub file: String,
+    ///Absolute path of `file`, for editors outside of cubensis
+    pub path: std::path::PathBuf,
+    ///1-based, 0 if the error has no location
+    pub line: usize,
+    ///1-based, 0 if the error has no location
+    pub column: usize,
+    pub message: String,
+    ///The offending source line with the error position labelled, empty if the error has no location
+    pub snippet: String,
+}
+
+impl ShaderDiagnostic {
+    ///A diagnostic without a location, for errors only known by their message
+    pub fn unlocated(shader: &str, message: String) -> Self {
+        Self {
+            shader: shader.to_string(),
+            file: shader.to_string(),
+            path: LibraryConfiguration::scene_library_path().join(shader),
+            line: 0,
+            column: 0,
+            message,
+            snippet: String::new(),
+        }
+    }
+
+    ///Recovers the diagnostic carried by `error`, or describes the error by its message
+    pub fn from_error(shader: &str, error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<ShaderDiagnostic>() {
+            Some(diagnostic) => diagnostic.clone(),
+            None => Self::unlocated(shader, format!("{:#}", error)),
+        }
+    }
+
+    ///Labels `column` of the source line `text`, in the style of compiler diagnostics
+    pub fn label_snippet(line: usize, column: usize, text: &str, label: &str) -> String {
+        let gutter = " ".repeat(line.to_string().len());
+        let marker = " ".repeat(column.saturating_sub(1));
+        format!(
+            "{} |\n{} | {}\n{} | {}^ {}",
+            gutter, line, text, gutter, marker, label
+        )
+    }
+}
+
+impl std::fmt::Display for ShaderDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}: {}", self.file, self.message)
+        } else {
+            write!(
+                f,
+                "{}:{}:{}: {}\n{}",
+                self.file, self.line, self.column, self.message, self.snippet
+            )
+        }
+    }
+}
+
+impl std::error::Error for ShaderDiagnostic {}
@@ -5,4 +5,36 @@ pub enum CubensisEvent {
     SceneChange(String),
     ///Contains the path of the file that has changed
     FileEdit(std::path::PathBuf),
+    ///Saves the next presented frame into the scene's folder
+    Screenshot(CaptureOptions),
+    ///Starts or stops writing every presented frame into the scene's folder
+    ToggleRecording(CaptureOptions),
+    ///Whether frames are being recorded, sent after every recording toggle whether or not it succeeded
+    RecordingChanged(bool),
+    ///Replaces the transform of every mesh with the given name
+    SetMeshTransform {
+        mesh: String,
+        transform: crate::scene::transform::Transform,
+    },
+    ///Latest build error of every shader that currently fails to build, empty once they all build
+    ShaderDiagnostics(Vec<crate::diagnostics::ShaderDiagnostic>),
+}
+
+///Options for reading back presented frames
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct CaptureOptions {
+    ///Factor applied to the window resolution
+    pub scale: f32,
+    ///Whether the GUI overlay is drawn into the captured image
+    pub include_gui: bool,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            include_gui: false,
+        }
+    }
 }
@@ -1,4 +1,5 @@
 pub mod configuration;
+pub mod diagnostics;
 pub mod events;
 pub mod plugins;
 pub mod scene;
@@ -1,11 +1,37 @@
+use crate::scene::instances::InstanceSource;
 use crate::scene::primitives::PrimitiveType;
-use crate::scene::shaders::{ComputeShader, RenderShader};
+use crate::scene::shaders::{ComputeShader, PassOperations, RenderShader};
+use crate::scene::transform::Transform;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct MeshDescriptor {
     pub name: String,
     pub geometry_source: GeometrySource,
     pub render_shaders: Vec<RenderShader>,
+    #[serde(default)]
+    pub pass_operations: PassOperations,
+    ///Instances drawn per render shader, particle systems draw one per particle instead
+    #[serde(default = "MeshDescriptor::default_instance_count")]
+    pub instance_count: u32,
+    #[serde(default)]
+    pub instances: Option<InstanceSource>,
+    ///Uploaded as the model and normal matrices, changeable at runtime
+    #[serde(default)]
+    pub transform: Transform,
+}
+
+impl MeshDescriptor {
+    ///Operations used while `render_shader` draws this mesh
+    pub fn pass_operations<'a>(&'a self, render_shader: &'a RenderShader) -> &'a PassOperations {
+        render_shader
+            .pass_operations
+            .as_ref()
+            .unwrap_or(&self.pass_operations)
+    }
+
+    fn default_instance_count() -> u32 {
+        1
+    }
 }
 
 impl Default for MeshDescriptor {
@@ -14,6 +40,10 @@ impl Default for MeshDescriptor {
             name: "Default Mesh".to_string(),
             geometry_source: GeometrySource::Primitive(PrimitiveType::Quad),
             render_shaders: vec![RenderShader::default(), RenderShader::default_second_pass()],
+            pass_operations: PassOperations::default(),
+            instance_count: 1,
+            instances: None,
+            transform: Transform::default(),
         }
     }
 }
@@ -22,4 +52,68 @@ impl Default for MeshDescriptor {
 pub enum GeometrySource {
     Primitive(PrimitiveType),
     ComputeShader(ComputeShader),
+    ParticleSystem(ParticleSystem),
+    ///A mesh file from the scene library, every triangle primitive of it is merged into one mesh
+    File {
+        path: String,
+        ///Detected from the file extension if unset
+        #[serde(default)]
+        format: Option<MeshFileFormat>,
+    },
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFileFormat {
+    ///Wavefront OBJ
+    Obj,
+    ///glTF 2.0, either `.gltf` or binary `.glb`
+    Gltf,
+}
+
+impl MeshFileFormat {
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "obj" => Some(MeshFileFormat::Obj),
+            "gltf" | "glb" => Some(MeshFileFormat::Gltf),
+            _ => None,
+        }
+    }
+}
+
+///Particles simulated on the GPU by an emit and an update compute shader, drawn as one instance per particle
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ParticleSystem {
+    pub name: String,
+    ///Initialises the particles spawned this frame
+    pub emit_shader: String,
+    ///Advances every particle each frame
+    pub update_shader: String,
+    ///Number of particles kept in the state buffer, spawning overwrites the oldest ones
+    #[serde(default = "ParticleSystem::default_capacity")]
+    pub capacity: u32,
+    ///Particles spawned per second
+    #[serde(default = "ParticleSystem::default_spawn_rate")]
+    pub spawn_rate: f32,
+    #[serde(default)]
+    pub rendering: ParticleRendering,
+}
+
+impl ParticleSystem {
+    fn default_capacity() -> u32 {
+        16384
+    }
+
+    fn default_spawn_rate() -> f32 {
+        1000.0
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParticleRendering {
+    ///A quad per particle, expanded by the vertex shader
+    #[default]
+    Billboards,
+    ///A single point per particle
+    Points,
 }
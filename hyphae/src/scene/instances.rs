@@ -0,0 +1,41 @@
+///Where the per instance attributes of a mesh come from
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub enum InstanceSource {
+    ///Listed in the scene, instances beyond the list keep the defaults
+    Static(Vec<InstanceDescriptor>),
+    ///Written every frame by a compute shader declaring `workgroup_size(64)`, one invocation per instance
+    ComputeShader { path: String, name: String },
+    ///Instances spread along X, each carrying one bin of the power spectrum
+    Spectrum,
+}
+
+impl InstanceSource {
+    ///Shader path of the instance compute shader, if any
+    pub fn compute_shader_path(&self) -> Option<&str> {
+        match self {
+            InstanceSource::ComputeShader { path, .. } => Some(path.as_str()),
+            _ => None,
+        }
+    }
+}
+
+///Attributes of a single instance, their meaning is up to the render shaders
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct InstanceDescriptor {
+    pub offset: [f32; 3],
+    pub scale: f32,
+    pub color: [f32; 4],
+    pub data: [f32; 4],
+}
+
+impl Default for InstanceDescriptor {
+    fn default() -> Self {
+        InstanceDescriptor {
+            offset: [0.0; 3],
+            scale: 1.0,
+            color: [1.0; 4],
+            data: [0.0; 4],
+        }
+    }
+}
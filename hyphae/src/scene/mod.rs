@@ -2,21 +2,62 @@ use crate::scene::assets::SceneTextures;
 
 pub mod assets;
 pub mod geometry;
+pub mod instances;
+pub mod post;
 pub mod primitives;
+pub mod render_graph;
 pub mod shaders;
+pub mod transform;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct Scene {
     pub name: String,
     pub meshes: Vec<crate::scene::geometry::MeshDescriptor>,
     pub textures: SceneTextures,
+    #[serde(default)]
+    pub render_graph: crate::scene::render_graph::RenderGraph,
+    ///Number of previous frames kept in the render history
+    #[serde(default = "Scene::default_history_depth")]
+    pub history_depth: u32,
+    ///Copies depth into the render history so shaders can read it
+    #[serde(default)]
+    pub expose_depth: bool,
+    ///Multisample count of the scene's render passes, overriding the graphics configuration
+    #[serde(default)]
+    pub sample_count: Option<u32>,
+    ///Size of the rendered frame, overriding the graphics configuration
+    #[serde(default)]
+    pub render_resolution: Option<crate::configuration::graphics::RenderResolution>,
+    #[serde(default)]
+    pub post_effects: Vec<crate::scene::post::PostEffect>,
+    ///Defined before the first line of every shader, for `#ifdef` and substitution
+    #[serde(default)]
+    pub shader_defines: std::collections::HashMap<String, String>,
+    ///Location of the scene file, if it was loaded from disk
+    #[serde(skip)]
+    pub path: Option<std::path::PathBuf>,
 }
 
 impl Scene {
+    fn default_history_depth() -> u32 {
+        1
+    }
+
     pub fn load_from_disk(path: &std::path::PathBuf) -> anyhow::Result<Self> {
         log::debug!("Loading scene from disk");
         let data = std::fs::read_to_string(path)?;
-        serde_json::from_str(data.as_str()).map_err(|e| e.into())
+        let mut scene: Self = serde_json::from_str(data.as_str())?;
+        scene.path = Some(path.clone());
+        Ok(scene)
+    }
+
+    ///The folder containing the scene file, or the scene library for scenes not loaded from disk
+    pub fn directory(&self) -> std::path::PathBuf {
+        self.path
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|directory| directory.to_path_buf())
+            .unwrap_or_else(crate::configuration::library::LibraryConfiguration::scene_library_path)
     }
 }
 
@@ -27,6 +68,14 @@ impl Default for Scene {
             name: "Default Scene".to_string(),
             meshes: vec![crate::scene::geometry::MeshDescriptor::default()],
             textures: SceneTextures::default(),
+            render_graph: crate::scene::render_graph::RenderGraph::default(),
+            history_depth: Scene::default_history_depth(),
+            expose_depth: false,
+            sample_count: None,
+            render_resolution: None,
+            post_effects: Vec::new(),
+            shader_defines: std::collections::HashMap::new(),
+            path: None,
         }
     }
 }
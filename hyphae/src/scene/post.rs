@@ -0,0 +1,211 @@
+///Effects applied in order to the rendered frame after it was stored in the render history, they only affect the presented frame
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub enum PostEffect {
+    ///Adds a blurred copy of the parts brighter than `threshold`
+    Bloom(BloomEffect),
+    ///Separable gaussian blur, `radius` in pixels between taps
+    GaussianBlur(GaussianBlurEffect),
+    ///`iterations` passes sampling diagonal neighbours at growing distances
+    KawaseBlur(KawaseBlurEffect),
+    ///Offsets the red and blue channels away from the center
+    ChromaticAberration(ChromaticAberrationEffect),
+    Vignette(VignetteEffect),
+    FilmGrain(FilmGrainEffect),
+    ///Snaps the frame to blocks of `size` pixels
+    Pixelate(PixelateEffect),
+    ///Mirrors `segments` slices around the center
+    Kaleidoscope(KaleidoscopeEffect),
+    ColorGrading(ColorGradingEffect),
+}
+
+///A post effect parameter, either a constant or `value + amount * feature`
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum Parameter {
+    Constant(f32),
+    Modulated {
+        value: f32,
+        feature: AudioFeature,
+        #[serde(default = "Parameter::default_amount")]
+        amount: f32,
+    },
+}
+
+impl Parameter {
+    fn default_amount() -> f32 {
+        1.0
+    }
+
+    ///Base value, modulating feature and modulation amount
+    pub fn parts(&self) -> (f32, AudioFeature, f32) {
+        match *self {
+            Parameter::Constant(value) => (value, AudioFeature::None, 0.0),
+            Parameter::Modulated {
+                value,
+                feature,
+                amount,
+            } => (value, feature, amount),
+        }
+    }
+}
+
+///Audio measurements post effect parameters can follow, computed every frame from the audio textures
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AudioFeature {
+    #[default]
+    None,
+    ///RMS of the waveform
+    Level,
+    ///Mean power below 250 Hz
+    Bass,
+    ///Mean power between 250 Hz and 2 kHz
+    Mid,
+    ///Mean power above 2 kHz
+    Treble,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct BloomEffect {
+    pub threshold: Parameter,
+    pub intensity: Parameter,
+    pub radius: Parameter,
+}
+
+impl Default for BloomEffect {
+    fn default() -> Self {
+        Self {
+            threshold: Parameter::Constant(0.8),
+            intensity: Parameter::Constant(0.5),
+            radius: Parameter::Constant(2.0),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct GaussianBlurEffect {
+    pub radius: Parameter,
+}
+
+impl Default for GaussianBlurEffect {
+    fn default() -> Self {
+        Self {
+            radius: Parameter::Constant(1.0),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct KawaseBlurEffect {
+    pub iterations: u32,
+    pub offset: Parameter,
+}
+
+impl Default for KawaseBlurEffect {
+    fn default() -> Self {
+        Self {
+            iterations: 4,
+            offset: Parameter::Constant(1.0),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct ChromaticAberrationEffect {
+    ///Offset at the corners, as a fraction of the frame
+    pub strength: Parameter,
+}
+
+impl Default for ChromaticAberrationEffect {
+    fn default() -> Self {
+        Self {
+            strength: Parameter::Constant(0.005),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct VignetteEffect {
+    pub intensity: Parameter,
+    ///Width of the transition towards the corners, from 0 to 1
+    pub smoothness: Parameter,
+}
+
+impl Default for VignetteEffect {
+    fn default() -> Self {
+        Self {
+            intensity: Parameter::Constant(0.5),
+            smoothness: Parameter::Constant(0.5),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct FilmGrainEffect {
+    pub intensity: Parameter,
+}
+
+impl Default for FilmGrainEffect {
+    fn default() -> Self {
+        Self {
+            intensity: Parameter::Constant(0.05),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct PixelateEffect {
+    pub size: Parameter,
+}
+
+impl Default for PixelateEffect {
+    fn default() -> Self {
+        Self {
+            size: Parameter::Constant(8.0),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct KaleidoscopeEffect {
+    pub segments: Parameter,
+    ///Rotation of the mirrored slices in degrees
+    pub rotation: Parameter,
+}
+
+impl Default for KaleidoscopeEffect {
+    fn default() -> Self {
+        Self {
+            segments: Parameter::Constant(6.0),
+            rotation: Parameter::Constant(0.0),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct ColorGradingEffect {
+    pub exposure: Parameter,
+    pub contrast: Parameter,
+    pub saturation: Parameter,
+    ///Hue rotation in degrees
+    pub hue_shift: Parameter,
+}
+
+impl Default for ColorGradingEffect {
+    fn default() -> Self {
+        Self {
+            exposure: Parameter::Constant(1.0),
+            contrast: Parameter::Constant(1.0),
+            saturation: Parameter::Constant(1.0),
+            hue_shift: Parameter::Constant(0.0),
+        }
+    }
+}
@@ -1,4 +1,117 @@
+///Built-in geometry, generated when the mesh is loaded.
+///Solids are centred on the origin; flat primitives lie in the XY plane like `Quad`.
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub enum PrimitiveType {
+    ///Two triangles covering clip space
     Quad,
+    Cube {
+        #[serde(default = "PrimitiveType::default_size")]
+        size: f32,
+    },
+    UvSphere {
+        #[serde(default = "PrimitiveType::default_radius")]
+        radius: f32,
+        ///Subdivisions around the vertical axis
+        #[serde(default = "PrimitiveType::default_segments")]
+        segments: u32,
+        ///Subdivisions from pole to pole
+        #[serde(default = "PrimitiveType::default_rings")]
+        rings: u32,
+    },
+    Icosphere {
+        #[serde(default = "PrimitiveType::default_radius")]
+        radius: f32,
+        ///Every level splits each triangle into four
+        #[serde(default = "PrimitiveType::default_subdivisions")]
+        subdivisions: u32,
+    },
+    Torus {
+        ///Distance from the centre to the middle of the tube
+        #[serde(default = "PrimitiveType::default_radius")]
+        major_radius: f32,
+        #[serde(default = "PrimitiveType::default_minor_radius")]
+        minor_radius: f32,
+        #[serde(default = "PrimitiveType::default_segments")]
+        major_segments: u32,
+        #[serde(default = "PrimitiveType::default_rings")]
+        minor_segments: u32,
+    },
+    ///Along the Y axis
+    Cylinder {
+        #[serde(default = "PrimitiveType::default_radius")]
+        radius: f32,
+        #[serde(default = "PrimitiveType::default_size")]
+        height: f32,
+        #[serde(default = "PrimitiveType::default_segments")]
+        segments: u32,
+        ///Closes the ends with discs
+        #[serde(default = "PrimitiveType::default_capped")]
+        capped: bool,
+    },
+    ///A grid of `columns` x `rows` cells, the default size covers clip space like `Quad`
+    PlaneGrid {
+        #[serde(default = "PrimitiveType::default_plane_size")]
+        width: f32,
+        #[serde(default = "PrimitiveType::default_plane_size")]
+        height: f32,
+        #[serde(default = "PrimitiveType::default_resolution")]
+        columns: u32,
+        #[serde(default = "PrimitiveType::default_resolution")]
+        rows: u32,
+    },
+    ///Line segments connecting `points` in order
+    LineStrip { points: Vec<[f32; 3]> },
+    ///A closed line loop
+    Circle {
+        #[serde(default = "PrimitiveType::default_radius")]
+        radius: f32,
+        #[serde(default = "PrimitiveType::default_segments")]
+        segments: u32,
+    },
+}
+
+impl PrimitiveType {
+    ///True for primitives drawn as lines rather than triangles
+    pub fn is_line(&self) -> bool {
+        matches!(
+            self,
+            PrimitiveType::LineStrip { .. } | PrimitiveType::Circle { .. }
+        )
+    }
+
+    fn default_size() -> f32 {
+        1.0
+    }
+
+    fn default_radius() -> f32 {
+        0.5
+    }
+
+    fn default_minor_radius() -> f32 {
+        0.2
+    }
+
+    fn default_segments() -> u32 {
+        32
+    }
+
+    fn default_rings() -> u32 {
+        16
+    }
+
+    fn default_subdivisions() -> u32 {
+        2
+    }
+
+    fn default_capped() -> bool {
+        true
+    }
+
+    fn default_plane_size() -> f32 {
+        2.0
+    }
+
+    fn default_resolution() -> u32 {
+        64
+    }
 }
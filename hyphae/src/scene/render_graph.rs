@@ -0,0 +1,67 @@
+///Name of the implicit target holding the frame that is presented and kept in the render history
+pub const OUTPUT_TARGET: &str = "output";
+
+///Named render targets and the passes that draw meshes into them.
+///An empty graph draws every mesh of the scene into the output in a single pass.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RenderGraph {
+    pub targets: Vec<RenderTargetDescriptor>,
+    pub passes: Vec<RenderPassDescriptor>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RenderTargetDescriptor {
+    pub name: String,
+    ///Fixed `[width, height]`, defaults to the output size
+    #[serde(default)]
+    pub size: Option<[u32; 2]>,
+    ///Multiplies the size, e.g. `0.5` for a half resolution blur target
+    #[serde(default = "RenderTargetDescriptor::default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub format: RenderTargetFormat,
+}
+
+impl RenderTargetDescriptor {
+    fn default_scale() -> f32 {
+        1.0
+    }
+
+    ///Size of the target when the output is `output_size` pixels large
+    pub fn resolve_size(&self, output_size: [u32; 2]) -> [u32; 2] {
+        let [width, height] = self.size.unwrap_or(output_size);
+        let scale = |dimension: u32| ((dimension as f32 * self.scale).round() as u32).max(1);
+        [scale(width), scale(height)]
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderTargetFormat {
+    ///Same format as the output
+    #[default]
+    Output,
+    Rgba8Unorm,
+    Rgba16Float,
+    Rgba32Float,
+    R32Float,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct RenderPassDescriptor {
+    pub name: String,
+    ///Names of the scene meshes drawn in this pass, in order
+    pub meshes: Vec<String>,
+    ///Targets sampled by the pass' shaders, bound in declaration order
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    ///Targets written by the pass, one colour attachment each
+    #[serde(default = "RenderPassDescriptor::default_outputs")]
+    pub outputs: Vec<String>,
+}
+
+impl RenderPassDescriptor {
+    fn default_outputs() -> Vec<String> {
+        vec![OUTPUT_TARGET.to_string()]
+    }
+}
@@ -1,5 +1,36 @@
 use crate::configuration::library::LibraryConfiguration;
 
+///Language of a shader file, inferred from its extension unless the scene declares it
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderLanguage {
+    Wgsl,
+    ///Vulkan GLSL, render shaders hold both stages in `#ifdef VERTEX` and `#ifdef FRAGMENT` blocks
+    Glsl,
+    ///Precompiled SPIR-V binary, render shaders hold both stages
+    SpirV,
+    ///Shadertoy image shader defining `mainImage`, wrapped into a GLSL render shader. Never inferred from the path.
+    Shadertoy,
+}
+
+impl ShaderLanguage {
+    ///`.glsl`, `.vert`, `.frag` and `.comp` files are GLSL, `.spv` files SPIR-V and any other WGSL
+    pub fn from_path(path: &str) -> Self {
+        match std::path::Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("glsl" | "vert" | "frag" | "comp") => ShaderLanguage::Glsl,
+            Some("spv") => ShaderLanguage::SpirV,
+            _ => ShaderLanguage::Wgsl,
+        }
+    }
+
+    ///GLSL and Shadertoy shaders are compiled into a module per stage
+    pub fn is_glsl(&self) -> bool {
+        matches!(self, ShaderLanguage::Glsl | ShaderLanguage::Shadertoy)
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy)]
 pub enum RenderShaderBlending {
     None,
@@ -7,11 +38,61 @@ pub enum RenderShaderBlending {
     AlphaBlending,
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadOperation {
+    Clear,
+    Load,
+}
+
+///Load/store behaviour of the attachments while a render shader draws
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct PassOperations {
+    ///`None` clears in the first draw of a render pass and loads in every later one
+    pub color_load: Option<LoadOperation>,
+    pub clear_color: [f64; 4],
+    pub color_store: bool,
+    ///`None` clears in the first draw of a render pass and loads in every later one
+    pub depth_load: Option<LoadOperation>,
+    pub clear_depth: f32,
+    pub depth_store: bool,
+}
+
+impl Default for PassOperations {
+    fn default() -> Self {
+        PassOperations {
+            color_load: None,
+            clear_color: [0.01, 0.01, 0.01, 1.0],
+            color_store: true,
+            depth_load: None,
+            clear_depth: 1.0,
+            depth_store: true,
+        }
+    }
+}
+
+impl PassOperations {
+    ///The load operation of a draw, filling in the default for unset operations
+    pub fn resolve_load(load: Option<LoadOperation>, is_first_draw: bool) -> LoadOperation {
+        match (load, is_first_draw) {
+            (Some(load), _) => load,
+            (None, true) => LoadOperation::Clear,
+            (None, false) => LoadOperation::Load,
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct RenderShader {
     pub path: String,
     pub name: String,
     pub blending: RenderShaderBlending,
+    ///Replaces the operations of the mesh for this shader only
+    #[serde(default)]
+    pub pass_operations: Option<PassOperations>,
+    ///Inferred from the file extension if missing
+    #[serde(default)]
+    pub language: Option<ShaderLanguage>,
 }
 
 impl Default for RenderShader {
@@ -20,14 +101,29 @@ impl Default for RenderShader {
             path: "../shaders/default_shader.wgsl".into(),
             name: "Default Render Shader".to_string(),
             blending: RenderShaderBlending::Replace,
+            pass_operations: None,
+            language: None,
         }
     }
 }
 
+///Generates the vertices and indices of a mesh on the GPU every frame
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct ComputeShader {
-    pub(crate) path: String,
-    pub(crate) name: String,
+    pub path: String,
+    pub name: String,
+    ///Capacity of the vertex storage buffer
+    #[serde(default = "ComputeShader::default_max_vertices")]
+    pub max_vertices: u32,
+    ///Capacity of the index storage buffer
+    #[serde(default = "ComputeShader::default_max_indices")]
+    pub max_indices: u32,
+    ///Workgroups dispatched every frame, `[x, y, z]`
+    #[serde(default = "ComputeShader::default_workgroups")]
+    pub workgroups: [u32; 3],
+    ///Inferred from the file extension if missing
+    #[serde(default)]
+    pub language: Option<ShaderLanguage>,
 }
 
 impl RenderShader {
@@ -38,6 +134,10 @@ impl RenderShader {
     pub fn get_blend_mode(&self) -> RenderShaderBlending {
         self.blending
     }
+    pub fn get_language(&self) -> ShaderLanguage {
+        self.language
+            .unwrap_or_else(|| ShaderLanguage::from_path(&self.path))
+    }
 
     pub fn from_json(json: String) -> anyhow::Result<Self> {
         let render_shader = serde_json::from_str(&json)?;
@@ -48,6 +148,8 @@ impl RenderShader {
             path: "../shaders/default_shader_second_pass.wgsl".into(),
             name: "Default Render Shader 2nd pass".to_string(),
             blending: RenderShaderBlending::AlphaBlending,
+            pass_operations: None,
+            language: None,
         }
     }
 }
@@ -57,4 +159,50 @@ impl ComputeShader {
         std::fs::read_to_string(LibraryConfiguration::scene_library_path().join(&self.path))
             .unwrap()
     }
+
+    pub fn get_language(&self) -> ShaderLanguage {
+        self.language
+            .unwrap_or_else(|| ShaderLanguage::from_path(&self.path))
+    }
+
+    fn default_max_vertices() -> u32 {
+        65536
+    }
+
+    fn default_max_indices() -> u32 {
+        3 * 65536
+    }
+
+    fn default_workgroups() -> [u32; 3] {
+        [1, 1, 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_from_path() {
+        assert_eq!(
+            ShaderLanguage::from_path("shader.wgsl"),
+            ShaderLanguage::Wgsl
+        );
+        assert_eq!(
+            ShaderLanguage::from_path("shaders/main.glsl"),
+            ShaderLanguage::Glsl
+        );
+        assert_eq!(ShaderLanguage::from_path("main.vert"), ShaderLanguage::Glsl);
+        assert_eq!(ShaderLanguage::from_path("main.frag"), ShaderLanguage::Glsl);
+        assert_eq!(
+            ShaderLanguage::from_path("particles.comp"),
+            ShaderLanguage::Glsl
+        );
+        assert_eq!(
+            ShaderLanguage::from_path("compiled.spv"),
+            ShaderLanguage::SpirV
+        );
+        assert_eq!(ShaderLanguage::from_path("glsl"), ShaderLanguage::Wgsl);
+        assert_eq!(ShaderLanguage::from_path("shader"), ShaderLanguage::Wgsl);
+    }
 }
@@ -0,0 +1,19 @@
+///Placement of a mesh, applied as scale, then rotation, then translation
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Transform {
+    pub translation: [f32; 3],
+    ///Euler angles in degrees, applied around X, then Y, then Z
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: [1.0; 3],
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::gui::CubensisGuiComponent;
+use crate::ResourceCollection;
+use egui::CtxRef;
+use epi::Frame;
+use hyphae::configuration::library::Library;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::events::CubensisEvent;
+use std::time::Duration;
+use winit::event::Event;
+
+///Lists the shaders failing to build over the canvas, shown even while the rest of the GUI is hidden
+pub struct DiagnosticsOverlay {
+    diagnostics: Vec<ShaderDiagnostic>,
+}
+
+impl DiagnosticsOverlay {
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl CubensisGuiComponent for DiagnosticsOverlay {
+    fn update(&mut self, _: Duration) {}
+
+    fn draw(&mut self, context: &CtxRef, _: &Frame, _: &Library, _: &ResourceCollection) {
+        if self.diagnostics.is_empty() {
+            return;
+        }
+        egui::Area::new("Shader Diagnostics")
+            .anchor(egui::Align2::LEFT_TOP, [8.0, 32.0])
+            .show(context, |ui| {
+                egui::Frame::popup(&context.style()).show(ui, |ui| {
+                    for diagnostic in &self.diagnostics {
+                        let location = if diagnostic.line == 0 {
+                            diagnostic.file.clone()
+                        } else {
+                            format!(
+                                "{}:{}:{}",
+                                diagnostic.file, diagnostic.line, diagnostic.column
+                            )
+                        };
+                        ui.add(
+                            egui::Label::new(format!("{}: {}", location, diagnostic.message))
+                                .strong()
+                                .text_color(egui::Color32::RED),
+                        );
+                        if !diagnostic.snippet.is_empty() {
+                            ui.add(egui::Label::new(&diagnostic.snippet).monospace());
+                        }
+                    }
+                });
+            });
+    }
+
+    fn handle_event(&mut self, event: &Event<CubensisEvent>) {
+        if let Event::UserEvent(CubensisEvent::ShaderDiagnostics(diagnostics)) = event {
+            self.diagnostics = diagnostics.clone();
+        }
+    }
+}
@@ -5,20 +5,26 @@ use crate::ResourceCollection;
 use egui::CtxRef;
 use epi::Frame;
 use hyphae::configuration::library::Library;
-use hyphae::events::CubensisEvent;
+use hyphae::events::{CaptureOptions, CubensisEvent};
 use std::time::Duration;
 use winit::event::Event;
 
 pub struct MainMenuAndWidgets {
     pub(crate) audio_widget_enabled: bool,
     pub(crate) camera_widget_enabled: bool,
+    event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>,
+    capture_options: CaptureOptions,
+    is_recording: bool,
 }
 
 impl MainMenuAndWidgets {
-    pub fn new() -> Self {
+    pub fn new(event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>) -> Self {
         Self {
             audio_widget_enabled: false,
             camera_widget_enabled: false,
+            event_proxy,
+            capture_options: CaptureOptions::default(),
+            is_recording: false,
         }
     }
 
@@ -30,8 +36,33 @@ impl MainMenuAndWidgets {
                 let widget = self as &mut dyn CubensisGuiWidget<CameraWidget>;
                 widget.draw_menu_option(ui);
             });
+            egui::menu::menu(ui, "Capture", |ui| {
+                self.draw_capture_menu(ui);
+            });
         });
     }
+
+    fn draw_capture_menu(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::Slider::new(&mut self.capture_options.scale, 0.25..=4.0).text("Scale"));
+        ui.checkbox(&mut self.capture_options.include_gui, "Include GUI");
+        if ui.button("Screenshot (F12)").clicked() {
+            log::debug!("Requesting screenshot from GUI");
+            self.event_proxy
+                .send_event(CubensisEvent::Screenshot(self.capture_options))
+                .ok();
+        }
+        let recording_label = if self.is_recording {
+            "Stop Recording (F10)"
+        } else {
+            "Start Recording (F10)"
+        };
+        if ui.button(recording_label).clicked() {
+            log::debug!("Toggling recording from GUI");
+            self.event_proxy
+                .send_event(CubensisEvent::ToggleRecording(self.capture_options))
+                .ok();
+        }
+    }
     fn draw_widgets(&self, context: &egui::CtxRef, resource_collection: &ResourceCollection) {
         let widget = self as &dyn CubensisGuiWidget<AudioWidget>;
         widget.draw(context, resource_collection);
@@ -59,5 +90,9 @@ impl CubensisGuiComponent for MainMenuAndWidgets {
         self.draw_widgets(context, resource_collection);
     }
 
-    fn handle_event(&mut self, _: &Event<CubensisEvent>) {}
+    fn handle_event(&mut self, event: &Event<CubensisEvent>) {
+        if let Event::UserEvent(CubensisEvent::RecordingChanged(is_recording)) = event {
+            self.is_recording = *is_recording;
+        }
+    }
 }
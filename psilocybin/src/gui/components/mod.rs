@@ -1,3 +1,5 @@
+pub mod diagnostics;
 pub mod library;
 pub mod menu;
 pub mod status;
+pub mod transforms;
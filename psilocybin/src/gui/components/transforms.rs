@@ -0,0 +1,123 @@
+use crate::gui::CubensisGuiComponent;
+use crate::ResourceCollection;
+use egui::CtxRef;
+use epi::Frame;
+use hyphae::configuration::library::Library;
+use hyphae::events::CubensisEvent;
+use hyphae::scene::transform::Transform;
+use std::collections::HashMap;
+use std::time::Duration;
+use winit::event::Event;
+
+pub struct TransformPanel {
+    is_enabled: bool,
+    event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>,
+    ///Transforms changed since the scene was loaded, by mesh name
+    transforms: HashMap<String, Transform>,
+}
+
+impl TransformPanel {
+    pub fn new(event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>) -> Self {
+        Self {
+            is_enabled: false,
+            event_proxy,
+            transforms: HashMap::new(),
+        }
+    }
+
+    ///True if any component changed
+    fn draw_vector(ui: &mut egui::Ui, label: &str, vector: &mut [f32; 3], speed: f32) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label(label);
+            for value in vector.iter_mut() {
+                changed |= ui.add(egui::DragValue::new(value).speed(speed)).changed();
+            }
+        });
+        changed
+    }
+}
+
+impl CubensisGuiComponent for TransformPanel {
+    fn update(&mut self, _: Duration) {}
+
+    fn draw(
+        &mut self,
+        context: &CtxRef,
+        _: &Frame,
+        library: &Library,
+        _resource_collection: &ResourceCollection,
+    ) {
+        if !self.is_enabled {
+            return;
+        }
+        egui::panel::SidePanel::new(egui::panel::Side::Right, "Transform Panel").show(
+            context,
+            |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.heading("Mesh Transforms");
+                });
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for mesh in &library.current_scene().meshes {
+                        let transform = self
+                            .transforms
+                            .entry(mesh.name.clone())
+                            .or_insert(mesh.transform);
+                        let mut changed = false;
+                        ui.collapsing(mesh.name.as_str(), |ui| {
+                            changed |= Self::draw_vector(
+                                ui,
+                                "Translation",
+                                &mut transform.translation,
+                                0.01,
+                            );
+                            changed |=
+                                Self::draw_vector(ui, "Rotation", &mut transform.rotation, 1.0);
+                            changed |= Self::draw_vector(ui, "Scale", &mut transform.scale, 0.01);
+                            if ui.button("Reset").clicked() {
+                                *transform = mesh.transform;
+                                changed = true;
+                            }
+                        });
+                        if changed {
+                            log::debug!("Changing transform of mesh {} from GUI", mesh.name);
+                            self.event_proxy
+                                .send_event(CubensisEvent::SetMeshTransform {
+                                    mesh: mesh.name.clone(),
+                                    transform: *transform,
+                                })
+                                .ok();
+                        }
+                    }
+                });
+            },
+        );
+    }
+
+    fn handle_event(&mut self, event: &Event<CubensisEvent>) {
+        match event {
+            Event::WindowEvent {
+                event:
+                    winit::event::WindowEvent::KeyboardInput {
+                        input:
+                            winit::event::KeyboardInput {
+                                state: winit::event::ElementState::Pressed,
+                                virtual_keycode: Some(winit::event::VirtualKeyCode::F2),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                log::debug!("Toggling Transform Panel");
+                self.is_enabled = !self.is_enabled
+            }
+            //keeps the panel in sync with transforms changed over RPC
+            Event::UserEvent(CubensisEvent::SetMeshTransform { mesh, transform }) => {
+                self.transforms.insert(mesh.clone(), *transform);
+            }
+            Event::UserEvent(CubensisEvent::SceneChange(_)) => self.transforms.clear(),
+            _ => {}
+        }
+    }
+}
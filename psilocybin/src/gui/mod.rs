@@ -1,7 +1,9 @@
+use crate::gui::components::diagnostics::DiagnosticsOverlay;
 use crate::gui::components::library::LibraryPanel;
 use crate::resources::ResourceCollection;
 use components::menu::MainMenuAndWidgets;
 use components::status::StatusBar;
+use components::transforms::TransformPanel;
 use hyphae::configuration::library::Library;
 use hyphae::events::CubensisEvent;
 use psilocyn::gui::CubensisGuiApp;
@@ -15,21 +17,24 @@ type GuiComponent = Box<dyn CubensisGuiComponent>;
 pub struct GuiApp {
     is_enabled: bool,
     components: Vec<GuiComponent>,
+    diagnostics_overlay: DiagnosticsOverlay,
 }
 
 impl GuiApp {}
 
 impl CubensisGuiApp<ResourceCollection> for GuiApp {
-    fn new() -> Self {
+    fn new(event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>) -> Self {
         let is_hidden = false;
         let components: Vec<GuiComponent> = vec![
-            Box::new(MainMenuAndWidgets::new()),
+            Box::new(MainMenuAndWidgets::new(event_proxy.clone())),
             Box::new(StatusBar::new()),
             Box::new(LibraryPanel::new()),
+            Box::new(TransformPanel::new(event_proxy)),
         ];
         Self {
             is_enabled: is_hidden,
             components,
+            diagnostics_overlay: DiagnosticsOverlay::new(),
         }
     }
 
@@ -47,6 +52,8 @@ impl CubensisGuiApp<ResourceCollection> for GuiApp {
         resource_collection: &ResourceCollection,
     ) {
         log::trace!("Drawing GUI app");
+        self.diagnostics_overlay
+            .draw(context, frame, library, resource_collection);
         if !self.is_enabled {
             return;
         }
@@ -56,6 +63,7 @@ impl CubensisGuiApp<ResourceCollection> for GuiApp {
     }
 
     fn handle_event(&mut self, event: &winit::event::Event<CubensisEvent>) {
+        self.diagnostics_overlay.handle_event(event);
         match event {
             Event::WindowEvent {
                 event: ref window_event,
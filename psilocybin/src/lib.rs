@@ -7,8 +7,8 @@ use psilocyn::renderer::Renderer;
 pub mod gui;
 pub mod resources;
 
-pub fn run<Plugins: 'static + CubensisPluginCollection, const HISTORY_DEPTH: usize>(
+pub fn run<Plugins: 'static + CubensisPluginCollection>(
     configuration: Configuration,
-) -> ! {
-    Renderer::<ResourceCollection, GuiApp, Plugins, HISTORY_DEPTH>::run(configuration)
+) -> anyhow::Result<()> {
+    Renderer::<ResourceCollection, GuiApp, Plugins>::run(configuration)
 }
@@ -3,6 +3,9 @@ use hyphae::events::CubensisEvent;
 use psilocyn::device::GraphicsDevice;
 use psilocyn::resources::CubensisResource;
 use std::time::Duration;
+use substrate::analysis::AudioAnalysisSource;
+use substrate::file_stream::AudioFileStream;
+use substrate::silent_stream::SilentStream;
 use substrate::stream_info::AudioStreamInfo;
 use substrate::wave_stream::WaveStream;
 use substrate::AudioStreamSource;
@@ -11,7 +14,7 @@ pub struct AudioResource {
     graphics: std::rc::Rc<GraphicsDevice>,
     binding_group: u32,
     binding_offset: u32,
-    wave_stream: WaveStream,
+    wave_stream: Box<dyn AudioAnalysisSource>,
     wave_texture_width: u32,
     spectrum_texture_width: u32,
     wave_texture: wgpu::Texture,
@@ -28,10 +31,9 @@ impl AudioResource {
         configuration: Configuration,
         binding_group: u32,
         binding_offset: u32,
-    ) -> Self {
+    ) -> anyhow::Result<Self> {
         log::debug!("Creating audio buffer resource");
-        let stream_source = AudioStreamSource::default_stream();
-        let mut wave_stream = WaveStream::new(stream_source, configuration.clone());
+        let mut wave_stream = Self::create_analysis_source(&graphics, configuration)?;
         let (wave_data, spectrum_data) = wave_stream.get_wave_and_spectrum_data();
         let wave_texture = Self::create_1d_texture(&graphics, wave_data, "Wave Data Texture");
         let spectrum_texture =
@@ -47,7 +49,7 @@ impl AudioResource {
             ..wgpu::SamplerDescriptor::default()
         });
         let audio_stream_info = wave_stream.get_stream_info();
-        Self {
+        Ok(Self {
             graphics,
             wave_stream,
             wave_texture_width,
@@ -60,7 +62,32 @@ impl AudioResource {
             binding_group,
             binding_offset,
             audio_stream_info,
-        }
+        })
+    }
+    ///Headless rendering without an audio file is silent rather than listening to an input device
+    fn create_analysis_source(
+        graphics: &GraphicsDevice,
+        configuration: Configuration,
+    ) -> anyhow::Result<Box<dyn AudioAnalysisSource>> {
+        Ok(match configuration.audio.get_input_file() {
+            Some(path) => match AudioFileStream::new(path, configuration.clone()) {
+                Ok(stream) => Box::new(stream),
+                Err(error) => {
+                    return Err(anyhow::Error::msg(format!(
+                        "Failed to load audio file {:?}, only WAV files are supported: {}",
+                        path, error
+                    )))
+                }
+            },
+            None if graphics.is_headless() => Box::new(SilentStream::new(configuration)),
+            None => match AudioStreamSource::default_stream() {
+                Ok(stream_source) => Box::new(WaveStream::new(stream_source, configuration)),
+                Err(error) => {
+                    log::warn!("Falling back to silence: {}", error);
+                    Box::new(SilentStream::new(configuration))
+                }
+            },
+        })
     }
     fn create_1d_texture(
         graphics: &std::rc::Rc<GraphicsDevice>,
@@ -89,9 +116,10 @@ impl AudioResource {
 }
 
 impl CubensisResource for AudioResource {
-    fn update(&mut self, _: Duration) -> bool {
+    fn update(&mut self, time_delta: Duration) -> bool {
         log::trace!("Updating audio buffer resource");
         let mut should_rebuild_bind_group = false;
+        self.wave_stream.advance(time_delta);
         let (wave_data, spectrum_data) = self.wave_stream.get_wave_and_spectrum_data();
         if wave_data.len() as u32 != self.wave_texture_width {
             self.wave_texture =
@@ -154,7 +182,9 @@ impl CubensisResource for AudioResource {
         vec![
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D1,
@@ -164,7 +194,9 @@ impl CubensisResource for AudioResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 1,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D1,
@@ -174,7 +206,9 @@ impl CubensisResource for AudioResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 2,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Sampler {
                     comparison: false,
                     filtering: false,
@@ -217,4 +251,35 @@ impl CubensisResource for AudioResource {
     fn binding_size() -> u32 {
         3
     }
+
+    fn wgsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"// Waveform and power spectrum (10 Hz to 8 kHz, linear) of the latest audio
+[[group({group}), binding({wave})]]
+var wave_texture: texture_1d<f32>;
+[[group({group}), binding({spectrum})]]
+var spectrum_texture: texture_1d<f32>;
+[[group({group}), binding({sampler})]]
+var audio_sampler: sampler;
+"#,
+            group = binding_group,
+            wave = binding_offset,
+            spectrum = binding_offset + 1,
+            sampler = binding_offset + 2
+        )
+    }
+
+    fn glsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"// Waveform and power spectrum (10 Hz to 8 kHz, linear) of the latest audio
+layout(set = {group}, binding = {wave}) uniform texture1D wave_texture;
+layout(set = {group}, binding = {spectrum}) uniform texture1D spectrum_texture;
+layout(set = {group}, binding = {sampler}) uniform sampler audio_sampler;
+"#,
+            group = binding_group,
+            wave = binding_offset,
+            spectrum = binding_offset + 1,
+            sampler = binding_offset + 2
+        )
+    }
 }
@@ -1,5 +1,6 @@
 use hyphae::events::CubensisEvent;
 use psilocyn::device::GraphicsDevice;
+use psilocyn::resources::view::ViewRegion;
 use psilocyn::resources::CubensisResource;
 use std::time::Duration;
 use wgpu::util::DeviceExt;
@@ -17,6 +18,7 @@ pub struct CameraResource {
     previous_mouse_position: Option<winit::dpi::PhysicalPosition<f64>>,
     mouse_button_pressed: [bool; 2],
     requires_update: bool,
+    view_region: ViewRegion,
 }
 impl CameraResource {
     pub fn new(
@@ -40,6 +42,7 @@ impl CameraResource {
         let previous_mouse_position = None;
         let mouse_button_pressed = [false, false];
         let requires_update = false;
+        let view_region = ViewRegion::full(size);
         Self {
             graphics,
             binding_group,
@@ -49,18 +52,26 @@ impl CameraResource {
             previous_mouse_position,
             mouse_button_pressed,
             requires_update,
+            view_region,
         }
     }
+
+    ///Uploads the camera, cropping its projections to the current view region
+    fn write_camera_buffer(&self) {
+        let mut camera = self.arcball_camera;
+        let clip_transform = cgmath::Matrix4::from(self.view_region.clip_transform());
+        camera.perspective_projection = clip_transform * camera.perspective_projection;
+        camera.projection = clip_transform * camera.projection;
+        self.graphics
+            .queue
+            .write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[camera]));
+    }
 }
 
 impl CubensisResource for CameraResource {
     fn update(&mut self, _time_delta: Duration) -> bool {
         if self.requires_update {
-            self.graphics.queue.write_buffer(
-                &self.camera_buffer,
-                0,
-                bytemuck::cast_slice(&[self.arcball_camera]),
-            );
+            self.write_camera_buffer();
             self.requires_update = false;
         }
         false
@@ -70,13 +81,17 @@ impl CubensisResource for CameraResource {
         let size = self.graphics.get_size();
         self.arcball_camera
             .resize(size.width as f32, size.height as f32);
+        self.view_region = ViewRegion::full(size);
+        self.requires_update = true;
     }
 
     fn get_bind_group_layout_entries(&self) -> Vec<BindGroupLayoutEntry> {
         log::trace!("Retrieving camera resource bind group layout entries");
         vec![wgpu::BindGroupLayoutEntry {
             binding: self.binding_offset,
-            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            visibility: wgpu::ShaderStages::VERTEX
+                | wgpu::ShaderStages::FRAGMENT
+                | wgpu::ShaderStages::COMPUTE,
             ty: wgpu::BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
                 has_dynamic_offset: false,
@@ -158,4 +173,56 @@ impl CubensisResource for CameraResource {
     fn binding_size() -> u32 {
         1
     }
+
+    fn wgsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"[[block]]
+struct Camera {{
+    translation: mat4x4<f32>;
+    center_translation: mat4x4<f32>;
+    rotation: vec4<f32>;
+    camera: mat4x4<f32>;
+    inv_camera: mat4x4<f32>;
+    zoom_speed: f32;
+    inv_screen: vec2<f32>;
+    perspective_projection: mat4x4<f32>;
+    projection: mat4x4<f32>;
+}};
+
+[[group({group}), binding({offset})]]
+var<uniform> camera: Camera;
+"#,
+            group = binding_group,
+            offset = binding_offset
+        )
+    }
+
+    fn glsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"layout(set = {group}, binding = {offset}) uniform Camera {{
+    mat4 translation;
+    mat4 center_translation;
+    vec4 rotation;
+    mat4 camera;
+    mat4 inv_camera;
+    float zoom_speed;
+    vec2 inv_screen;
+    mat4 perspective_projection;
+    mat4 projection;
+}} camera;
+"#,
+            group = binding_group,
+            offset = binding_offset
+        )
+    }
+
+    fn set_view_region(&mut self, region: &ViewRegion) {
+        log::trace!("Setting camera view region to {:?}", region);
+        let full_width = region.full_size.width as f32;
+        let full_height = region.full_size.height as f32;
+        self.arcball_camera.resize(full_width, full_height);
+        self.arcball_camera.set_aspect_ratio(full_width / full_height);
+        self.view_region = *region;
+        self.write_camera_buffer();
+    }
 }
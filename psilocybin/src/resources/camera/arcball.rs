@@ -115,6 +115,12 @@ impl ArcballCamera {
         self.inv_screen[0] = f32::one() / width;
         self.inv_screen[1] = f32::one() / height;
     }
+    /// Rebuild the perspective projection for a new `width / height` aspect ratio.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.perspective_projection =
+            cgmath::perspective(cgmath::Deg(80.0), aspect_ratio, 0.01, 200.0);
+        self.update_camera();
+    }
     fn update_camera(&mut self) {
         self.camera = self.translation * Matrix4::from(self.rotation) * self.center_translation;
         self.inv_camera = self.camera.invert().unwrap();
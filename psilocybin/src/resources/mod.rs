@@ -2,9 +2,11 @@ use crate::resources::audio::AudioResource;
 use crate::resources::camera::CameraResource;
 use crate::resources::textures::TextureResource;
 use crate::resources::time::TimeResource;
+use crate::resources::view::ViewResource;
 use hyphae::configuration::Configuration;
 use hyphae::events::CubensisEvent;
 use psilocyn::device::GraphicsDevice;
+use psilocyn::resources::view::ViewRegion;
 use psilocyn::resources::{CubensisResource, CubensisResourceCollection};
 use std::rc::Rc;
 
@@ -12,6 +14,7 @@ pub mod audio;
 pub mod camera;
 pub mod textures;
 pub mod time;
+pub mod view;
 
 pub struct ResourceCollection {
     graphics: Rc<GraphicsDevice>,
@@ -21,31 +24,27 @@ pub struct ResourceCollection {
     pub audio: AudioResource,
     pub camera: CameraResource,
     pub textures: TextureResource,
+    pub view: ViewResource,
 }
 
 impl CubensisResourceCollection for ResourceCollection {
-    fn new(graphics: Rc<GraphicsDevice>, configuration: Configuration) -> Self {
+    fn new(graphics: Rc<GraphicsDevice>, configuration: Configuration) -> anyhow::Result<Self> {
         log::debug!("Creating resource collection");
-        let time = TimeResource::new(graphics.clone(), 0, 0);
-        let camera = CameraResource::new(graphics.clone(), 0, time.next_binding_offset_in_group());
+        let [time_offset, camera_offset, audio_offset, textures_offset, view_offset] =
+            binding_offsets();
+        let time = TimeResource::new(graphics.clone(), 0, time_offset);
+        let camera = CameraResource::new(graphics.clone(), 0, camera_offset);
 
-        let audio = AudioResource::new(
-            graphics.clone(),
-            configuration.clone(),
-            0,
-            camera.next_binding_offset_in_group(),
-        );
-        let textures = TextureResource::new(
-            graphics.clone(),
-            configuration.clone(),
-            0,
-            audio.next_binding_offset_in_group(),
-        );
+        let audio = AudioResource::new(graphics.clone(), configuration.clone(), 0, audio_offset)?;
+        let textures =
+            TextureResource::new(graphics.clone(), configuration.clone(), 0, textures_offset);
+        let view = ViewResource::new(graphics.clone(), 0, view_offset);
         let bind_group_layout_entries: Vec<wgpu::BindGroupLayoutEntry> = vec![
             time.get_bind_group_layout_entries(),
             camera.get_bind_group_layout_entries(),
             audio.get_bind_group_layout_entries(),
             textures.get_bind_group_layout_entries(),
+            view.get_bind_group_layout_entries(),
         ]
         .iter()
         .flat_map(|e| e.iter())
@@ -58,6 +57,7 @@ impl CubensisResourceCollection for ResourceCollection {
             camera.get_bind_group_entries(),
             audio.get_bind_group_entries(),
             textures.get_bind_group_entries(),
+            view.get_bind_group_entries(),
         ]
         .iter()
         .flat_map(|e| e.iter())
@@ -65,7 +65,7 @@ impl CubensisResourceCollection for ResourceCollection {
         .collect();
         let bind_group =
             create_bind_group(&graphics, &bind_group_layout, bind_group_entries.as_slice());
-        Self {
+        Ok(Self {
             graphics,
             bind_group_layout,
             bind_group,
@@ -73,7 +73,8 @@ impl CubensisResourceCollection for ResourceCollection {
             audio,
             camera,
             textures,
-        }
+            view,
+        })
     }
 
     fn update(&mut self, time_delta: std::time::Duration) {
@@ -87,6 +88,7 @@ impl CubensisResourceCollection for ResourceCollection {
                 self.camera.get_bind_group_layout_entries(),
                 self.audio.get_bind_group_layout_entries(),
                 self.textures.get_bind_group_layout_entries(),
+                self.view.get_bind_group_layout_entries(),
             ]
             .iter()
             .flat_map(|e| e.iter())
@@ -99,6 +101,7 @@ impl CubensisResourceCollection for ResourceCollection {
                 self.camera.get_bind_group_entries(),
                 self.audio.get_bind_group_entries(),
                 self.textures.get_bind_group_entries(),
+                self.view.get_bind_group_entries(),
             ]
             .iter()
             .flat_map(|e| e.iter())
@@ -116,6 +119,7 @@ impl CubensisResourceCollection for ResourceCollection {
         log::trace!("Resizing resource collection");
         self.time.resize();
         self.camera.resize();
+        self.view.resize();
     }
 
     fn get_bind_group_layouts(&self) -> Vec<&wgpu::BindGroupLayout> {
@@ -134,9 +138,60 @@ impl CubensisResourceCollection for ResourceCollection {
         self.time.handle_or_capture_event(event);
         self.camera.handle_or_capture_event(event);
         self.textures.handle_or_capture_event(event);
+        self.view.handle_or_capture_event(event);
+    }
+
+    fn set_view_region(&mut self, region: &ViewRegion) {
+        log::trace!("Setting resource collection view region");
+        self.camera.set_view_region(region);
+        self.view.set_view_region(region);
+    }
+
+    fn wgsl_prelude() -> String {
+        log::debug!("Generating resource collection WGSL prelude");
+        let [time_offset, camera_offset, audio_offset, textures_offset, view_offset] =
+            binding_offsets();
+        let declarations = [
+            TimeResource::wgsl_declarations(0, time_offset),
+            CameraResource::wgsl_declarations(0, camera_offset),
+            AudioResource::wgsl_declarations(0, audio_offset),
+            TextureResource::wgsl_declarations(0, textures_offset),
+            ViewResource::wgsl_declarations(0, view_offset),
+        ];
+        format!(
+            "// Generated from the resource collection, do not edit\n\n{}",
+            declarations.join("\n")
+        )
+    }
+
+    fn glsl_prelude() -> String {
+        log::debug!("Generating resource collection GLSL prelude");
+        let [time_offset, camera_offset, audio_offset, textures_offset, view_offset] =
+            binding_offsets();
+        let declarations = [
+            TimeResource::glsl_declarations(0, time_offset),
+            CameraResource::glsl_declarations(0, camera_offset),
+            AudioResource::glsl_declarations(0, audio_offset),
+            TextureResource::glsl_declarations(0, textures_offset),
+            ViewResource::glsl_declarations(0, view_offset),
+        ];
+        format!(
+            "// Generated from the resource collection, do not edit\n\n{}",
+            declarations.join("\n")
+        )
     }
 }
 
+///Binding offsets of time, camera, audio, textures and view in group 0,
+///shared by the bind group built in `new` and the generated preludes
+fn binding_offsets() -> [u32; 5] {
+    let camera_offset = TimeResource::binding_size();
+    let audio_offset = camera_offset + CameraResource::binding_size();
+    let textures_offset = audio_offset + AudioResource::binding_size();
+    let view_offset = textures_offset + TextureResource::binding_size();
+    [0, camera_offset, audio_offset, textures_offset, view_offset]
+}
+
 fn create_bind_group_layout(
     graphics: &Rc<GraphicsDevice>,
     entries: &[wgpu::BindGroupLayoutEntry],
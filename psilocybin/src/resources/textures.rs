@@ -170,7 +170,9 @@ impl CubensisResource for TextureResource {
         vec![
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D2,
@@ -180,7 +182,9 @@ impl CubensisResource for TextureResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 1,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D2,
@@ -190,7 +194,9 @@ impl CubensisResource for TextureResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 2,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D2,
@@ -200,7 +206,9 @@ impl CubensisResource for TextureResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 3,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D2,
@@ -210,7 +218,9 @@ impl CubensisResource for TextureResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 4,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Texture {
                     multisampled: false,
                     view_dimension: wgpu::TextureViewDimension::D2,
@@ -220,7 +230,9 @@ impl CubensisResource for TextureResource {
             },
             wgpu::BindGroupLayoutEntry {
                 binding: self.binding_offset + 5,
-                visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                visibility: wgpu::ShaderStages::VERTEX
+                    | wgpu::ShaderStages::FRAGMENT
+                    | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Sampler {
                     comparison: false,
                     filtering: false,
@@ -274,4 +286,40 @@ impl CubensisResource for TextureResource {
     fn binding_size() -> u32 {
         6
     }
+
+    fn wgsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        let mut declarations = String::from("// Scene textures texture_1 to texture_5\n");
+        for index in 0..5 {
+            declarations.push_str(&format!(
+                "[[group({}), binding({})]]\nvar texture_{}: texture_2d<f32>;\n",
+                binding_group,
+                binding_offset + index,
+                index + 1
+            ));
+        }
+        declarations.push_str(&format!(
+            "[[group({}), binding({})]]\nvar texture_sampler: sampler;\n",
+            binding_group,
+            binding_offset + 5
+        ));
+        declarations
+    }
+
+    fn glsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        let mut declarations = String::from("// Scene textures texture_1 to texture_5\n");
+        for index in 0..5 {
+            declarations.push_str(&format!(
+                "layout(set = {}, binding = {}) uniform texture2D texture_{};\n",
+                binding_group,
+                binding_offset + index,
+                index + 1
+            ));
+        }
+        declarations.push_str(&format!(
+            "layout(set = {}, binding = {}) uniform sampler texture_sampler;\n",
+            binding_group,
+            binding_offset + 5
+        ));
+        declarations
+    }
 }
@@ -8,6 +8,7 @@ pub struct TimeResource {
     binding_group: u32,
     binding_offset: u32,
     program_start_time: std::time::Instant,
+    elapsed_time: std::time::Duration,
     frame_count: u32,
     time_buffer_data: TimeBufferData,
     time_buffer: wgpu::Buffer,
@@ -22,6 +23,7 @@ impl TimeResource {
     ) -> Self {
         log::trace!("Creating time resource");
         let program_start_time = std::time::Instant::now();
+        let elapsed_time = std::time::Duration::ZERO;
         let frame_count = 0 as u32;
         let time_buffer_data = TimeBufferData::new();
         let time_buffer = graphics
@@ -35,6 +37,7 @@ impl TimeResource {
         Self {
             graphics,
             program_start_time,
+            elapsed_time,
             frame_count,
             time_buffer_data,
             time_buffer,
@@ -48,9 +51,10 @@ impl TimeResource {
         log::trace!("Retrieving program start time");
         self.program_start_time.clone()
     }
+    ///Sum of all frame time deltas, so offline rendering at a fixed step stays deterministic
     pub fn get_elapsed_time(&self) -> std::time::Duration {
         log::trace!("Retrieving time elapsed since program started");
-        self.program_start_time.elapsed()
+        self.elapsed_time
     }
     pub fn get_frame_count(&self) -> u32 {
         log::trace!("Retrieving current frame index");
@@ -66,6 +70,7 @@ impl CubensisResource for TimeResource {
     fn update(&mut self, time_delta: std::time::Duration) -> bool {
         log::trace!("Updating time resource");
         self.frame_count += 1;
+        self.elapsed_time += time_delta;
         self.average_fps = 1.0 / time_delta.as_secs_f32();
         self.time_buffer_data
             .update(self.get_frame_count(), self.get_elapsed_time(), time_delta);
@@ -83,7 +88,9 @@ impl CubensisResource for TimeResource {
         log::trace!("Retrieving time resource bind group layout entries");
         vec![wgpu::BindGroupLayoutEntry {
             binding: self.binding_offset,
-            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            visibility: wgpu::ShaderStages::VERTEX
+                | wgpu::ShaderStages::FRAGMENT
+                | wgpu::ShaderStages::COMPUTE,
             ty: wgpu::BindingType::Buffer {
                 ty: wgpu::BufferBindingType::Uniform,
                 has_dynamic_offset: false,
@@ -116,6 +123,36 @@ impl CubensisResource for TimeResource {
     fn binding_size() -> u32 {
         1
     }
+
+    fn wgsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"[[block]]
+struct TimeInfo {{
+    frame_index: u32;
+    time_seconds: f32;
+    frame_time_seconds: f32;
+}};
+
+[[group({group}), binding({offset})]]
+var<uniform> time_info: TimeInfo;
+"#,
+            group = binding_group,
+            offset = binding_offset
+        )
+    }
+
+    fn glsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"layout(set = {group}, binding = {offset}) uniform TimeInfo {{
+    uint frame_index;
+    float time_seconds;
+    float frame_time_seconds;
+}} time_info;
+"#,
+            group = binding_group,
+            offset = binding_offset
+        )
+    }
 }
 
 #[repr(C)]
@@ -0,0 +1,212 @@
+use hyphae::events::CubensisEvent;
+use psilocyn::device::GraphicsDevice;
+use psilocyn::resources::view::ViewRegion;
+use psilocyn::resources::CubensisResource;
+use wgpu::util::DeviceExt;
+use winit::event::{ElementState, Event, MouseButton, WindowEvent};
+
+///Exposes the full image resolution, the region of it covered by the current frame and the mouse
+pub struct ViewResource {
+    graphics: std::rc::Rc<GraphicsDevice>,
+    binding_group: u32,
+    binding_offset: u32,
+    cursor_position: [f32; 2],
+    mouse_pressed: bool,
+    view_buffer_data: ViewBufferData,
+    view_buffer: wgpu::Buffer,
+}
+
+impl ViewResource {
+    pub fn new(
+        graphics: std::rc::Rc<GraphicsDevice>,
+        binding_group: u32,
+        binding_offset: u32,
+    ) -> Self {
+        log::trace!("Creating view resource");
+        let view_buffer_data =
+            ViewBufferData::new(&ViewRegion::full(graphics.get_render_size()), [0.0; 4]);
+        let view_buffer = graphics
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("View Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[view_buffer_data]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        Self {
+            graphics,
+            binding_group,
+            binding_offset,
+            cursor_position: [0.0; 2],
+            mouse_pressed: false,
+            view_buffer_data,
+            view_buffer,
+        }
+    }
+
+    ///Window cursor position in pixels of the full image, with the origin at the bottom left
+    fn image_position(&self, position: &winit::dpi::PhysicalPosition<f64>) -> [f32; 2] {
+        let window_size = self.graphics.get_size();
+        let window_width = window_size.width.max(1) as f32;
+        let window_height = window_size.height.max(1) as f32;
+        let [width, height] = self.view_buffer_data.resolution;
+        [
+            position.x as f32 * width / window_width,
+            (window_height - position.y as f32) * height / window_height,
+        ]
+    }
+
+    fn write_view_buffer(&self) {
+        self.graphics.queue.write_buffer(
+            &self.view_buffer,
+            0,
+            bytemuck::cast_slice(&[self.view_buffer_data]),
+        );
+    }
+}
+
+impl CubensisResource for ViewResource {
+    fn update(&mut self, _time_delta: std::time::Duration) -> bool {
+        log::trace!("Updating view resource");
+        false
+    }
+    fn resize(&mut self) {
+        log::trace!("Resizing view resource");
+        self.view_buffer_data = ViewBufferData::new(
+            &ViewRegion::full(self.graphics.get_render_size()),
+            self.view_buffer_data.mouse,
+        );
+        self.write_view_buffer();
+    }
+    fn get_bind_group_layout_entries(&self) -> Vec<wgpu::BindGroupLayoutEntry> {
+        log::trace!("Retrieving view resource bind group layout entries");
+        vec![wgpu::BindGroupLayoutEntry {
+            binding: self.binding_offset,
+            visibility: wgpu::ShaderStages::VERTEX
+                | wgpu::ShaderStages::FRAGMENT
+                | wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }]
+    }
+    fn get_bind_group_entries(&self) -> Vec<wgpu::BindGroupEntry> {
+        log::trace!("Retrieving view resource bind group entries");
+        vec![wgpu::BindGroupEntry {
+            binding: self.binding_offset,
+            resource: self.view_buffer.as_entire_binding(),
+        }]
+    }
+
+    ///Tracks the mouse like Shadertoy's `iMouse`: xy follows the cursor while the left button is held,
+    ///zw is where it was pressed, negated once released
+    fn handle_or_capture_event(&mut self, event: &Event<'_, CubensisEvent>) -> bool {
+        log::trace!("Handling event in view resource");
+        if let Event::WindowEvent { ref event, .. } = event {
+            match event {
+                WindowEvent::CursorMoved { position, .. } => {
+                    self.cursor_position = self.image_position(position);
+                    if self.mouse_pressed {
+                        self.view_buffer_data.mouse[0] = self.cursor_position[0];
+                        self.view_buffer_data.mouse[1] = self.cursor_position[1];
+                        self.write_view_buffer();
+                    }
+                }
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    self.mouse_pressed = state == &ElementState::Pressed;
+                    let [x, y] = self.cursor_position;
+                    self.view_buffer_data.mouse = if self.mouse_pressed {
+                        [x, y, x, y]
+                    } else {
+                        let [x, y, click_x, click_y] = self.view_buffer_data.mouse;
+                        [x, y, -click_x.abs(), -click_y.abs()]
+                    };
+                    self.write_view_buffer();
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn binding_group(&self) -> u32 {
+        self.binding_group
+    }
+
+    fn binding_offset(&self) -> u32 {
+        self.binding_offset
+    }
+
+    fn binding_size() -> u32 {
+        1
+    }
+
+    fn wgsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"// Size of the whole image and the region of it being rendered, in pixels,
+// mouse as Shadertoy's iMouse in pixels of the whole image
+[[block]]
+struct ViewInfo {{
+    resolution: vec2<f32>;
+    region_offset: vec2<f32>;
+    region_size: vec2<f32>;
+    mouse: vec4<f32>;
+}};
+
+[[group({group}), binding({offset})]]
+var<uniform> view_info: ViewInfo;
+"#,
+            group = binding_group,
+            offset = binding_offset
+        )
+    }
+
+    fn glsl_declarations(binding_group: u32, binding_offset: u32) -> String {
+        format!(
+            r#"// Size of the whole image and the region of it being rendered, in pixels,
+// mouse as Shadertoy's iMouse in pixels of the whole image
+layout(set = {group}, binding = {offset}) uniform ViewInfo {{
+    vec2 resolution;
+    vec2 region_offset;
+    vec2 region_size;
+    vec4 mouse;
+}} view_info;
+"#,
+            group = binding_group,
+            offset = binding_offset
+        )
+    }
+
+    fn set_view_region(&mut self, region: &ViewRegion) {
+        log::trace!("Setting view resource region to {:?}", region);
+        self.view_buffer_data = ViewBufferData::new(region, self.view_buffer_data.mouse);
+        self.write_view_buffer();
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ViewBufferData {
+    resolution: [f32; 2],
+    region_offset: [f32; 2],
+    region_size: [f32; 2],
+    _padding: [f32; 2],
+    mouse: [f32; 4],
+}
+impl ViewBufferData {
+    fn new(region: &ViewRegion, mouse: [f32; 4]) -> Self {
+        Self {
+            resolution: [region.full_size.width as f32, region.full_size.height as f32],
+            region_offset: [region.offset.x as f32, region.offset.y as f32],
+            region_size: [region.size.width as f32, region.size.height as f32],
+            _padding: [0.0; 2],
+            mouse,
+        }
+    }
+}
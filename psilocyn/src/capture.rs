@@ -0,0 +1,260 @@
+use crate::device::GraphicsDevice;
+use crate::readback::TextureReadback;
+use hyphae::events::CaptureOptions;
+use hyphae::scene::Scene;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::mpsc;
+
+type MappingFuture = Pin<Box<dyn Future<Output = Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+///Reads presented frames back from the GPU and writes them to disk on a background thread
+pub(crate) struct FrameCapture {
+    graphics: Rc<GraphicsDevice>,
+    screenshot: Option<(CaptureOptions, PathBuf)>,
+    recording: Option<Recording>,
+    idle_targets: Vec<CaptureTarget>,
+    pending_targets: Vec<(CaptureTarget, MappingFuture)>,
+    writer: Option<mpsc::Sender<(image::RgbaImage, PathBuf)>>,
+    writer_thread: Option<std::thread::JoinHandle<()>>,
+}
+
+struct Recording {
+    options: CaptureOptions,
+    directory: PathBuf,
+    frame_index: u64,
+}
+
+impl FrameCapture {
+    pub fn new(graphics: Rc<GraphicsDevice>) -> Self {
+        log::debug!("Creating frame capture");
+        let (writer, receiver) = mpsc::channel::<(image::RgbaImage, PathBuf)>();
+        let writer_thread = std::thread::spawn(move || {
+            log::debug!("Starting capture writer thread");
+            for (image, path) in receiver {
+                match image.save(&path) {
+                    Ok(_) => log::debug!("Saved capture to {:?}", path),
+                    Err(e) => log::error!("Failed to save capture to {:?}: {}", path, e),
+                }
+            }
+            log::debug!("Capture writer thread finished");
+        });
+        Self {
+            graphics,
+            screenshot: None,
+            recording: None,
+            idle_targets: Vec::new(),
+            pending_targets: Vec::new(),
+            writer: Some(writer),
+            writer_thread: Some(writer_thread),
+        }
+    }
+
+    pub fn request_screenshot(&mut self, options: CaptureOptions, scene: &Scene) {
+        log::debug!("Requesting screenshot with {:?}", options);
+        let path = scene
+            .directory()
+            .join(format!("{}.png", Self::capture_name(scene)));
+        self.screenshot = Some((options, path));
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn toggle_recording(
+        &mut self,
+        options: CaptureOptions,
+        scene: &Scene,
+    ) -> anyhow::Result<()> {
+        match self.recording.take() {
+            Some(recording) => {
+                log::info!(
+                    "Stopped recording after {} frames into {:?}",
+                    recording.frame_index,
+                    recording.directory
+                );
+            }
+            None => {
+                let directory = scene
+                    .directory()
+                    .join("recordings")
+                    .join(Self::capture_name(scene));
+                std::fs::create_dir_all(&directory)?;
+                log::info!("Recording frames into {:?}", directory);
+                self.recording = Some(Recording {
+                    options,
+                    directory,
+                    frame_index: 0,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    ///Returns the targets the current frame has to be presented into in addition to the surface
+    pub fn begin_frame(&mut self) -> Vec<CaptureTarget> {
+        let mut targets = Vec::new();
+        if let Some((options, path)) = self.screenshot.take() {
+            targets.push(self.acquire_target(options, path));
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            recording.frame_index += 1;
+            let path = recording
+                .directory
+                .join(format!("frame_{:06}.png", recording.frame_index));
+            let options = recording.options;
+            targets.push(self.acquire_target(options, path));
+        }
+        targets
+    }
+
+    ///Starts reading back targets whose copies have been submitted
+    pub fn end_frame(&mut self, targets: Vec<CaptureTarget>) {
+        for target in targets {
+            let mapping: MappingFuture = Box::pin(target.readback.map());
+            self.pending_targets.push((target, mapping));
+        }
+    }
+
+    ///Hands every finished readback to the writer thread without blocking
+    pub fn poll(&mut self) {
+        if self.pending_targets.is_empty() {
+            return;
+        }
+        log::trace!("Polling {} pending captures", self.pending_targets.len());
+        self.graphics.device.poll(wgpu::Maintain::Poll);
+        let mut context = std::task::Context::from_waker(futures::task::noop_waker_ref());
+        let mut still_pending = Vec::with_capacity(self.pending_targets.len());
+        for (target, mut mapping) in self.pending_targets.drain(..) {
+            match mapping.as_mut().poll(&mut context) {
+                std::task::Poll::Pending => still_pending.push((target, mapping)),
+                std::task::Poll::Ready(result) => {
+                    match result
+                        .map_err(|e| e.into())
+                        .and_then(|_| target.readback.read_mapped())
+                    {
+                        Ok(image) => {
+                            if let Some(writer) = self.writer.as_ref() {
+                                writer.send((image, target.path.clone())).ok();
+                            }
+                        }
+                        Err(e) => log::error!("Failed to read back capture: {}", e),
+                    }
+                    self.idle_targets.push(target);
+                }
+            }
+        }
+        self.pending_targets = still_pending;
+    }
+
+    fn acquire_target(&mut self, options: CaptureOptions, path: PathBuf) -> CaptureTarget {
+        let size = self.capture_size(options.scale);
+        let format = self.graphics.get_format();
+        self.idle_targets
+            .retain(|target| target.size == size && target.format == format);
+        let mut target = match self.idle_targets.pop() {
+            Some(target) => target,
+            None => CaptureTarget::new(&self.graphics.device, size, format),
+        };
+        target.include_gui = options.include_gui;
+        target.path = path;
+        target
+    }
+
+    fn capture_size(&self, scale: f32) -> wgpu::Extent3d {
+        let size = self.graphics.create_render_extent3d(1);
+        let max_dimension = self.graphics.max_texture_dimension();
+        //a single scale for both dimensions keeps the aspect ratio when the larger one hits the limit
+        let scale = scale.min(max_dimension as f32 / size.width.max(size.height) as f32);
+        let scale_dimension =
+            |dimension: u32| ((dimension as f32 * scale).round() as u32).clamp(1, max_dimension);
+        wgpu::Extent3d {
+            width: scale_dimension(size.width),
+            height: scale_dimension(size.height),
+            depth_or_array_layers: 1,
+        }
+    }
+
+    fn capture_name(scene: &Scene) -> String {
+        let name: String = scene
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        format!("{}_{}", name, timestamp)
+    }
+}
+
+impl Drop for FrameCapture {
+    fn drop(&mut self) {
+        log::debug!("Flushing pending captures");
+        if !self.pending_targets.is_empty() {
+            self.graphics.device.poll(wgpu::Maintain::Wait);
+            self.poll();
+        }
+        self.writer = None;
+        if let Some(writer_thread) = self.writer_thread.take() {
+            writer_thread.join().ok();
+        }
+    }
+}
+
+///Offscreen texture a frame is presented into before being copied to a readback buffer
+pub(crate) struct CaptureTarget {
+    texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+    readback: TextureReadback,
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+    include_gui: bool,
+    path: PathBuf,
+}
+
+impl CaptureTarget {
+    fn new(device: &wgpu::Device, size: wgpu::Extent3d, format: wgpu::TextureFormat) -> Self {
+        log::trace!("Creating capture target at {}x{}", size.width, size.height);
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let readback = TextureReadback::new(device, size, format);
+        Self {
+            texture,
+            texture_view,
+            readback,
+            size,
+            format,
+            include_gui: false,
+            path: PathBuf::new(),
+        }
+    }
+
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.texture_view
+    }
+
+    pub fn size(&self) -> wgpu::Extent3d {
+        self.size
+    }
+
+    pub fn include_gui(&self) -> bool {
+        self.include_gui
+    }
+
+    pub fn copy_to_readback(&self, encoder: &mut wgpu::CommandEncoder) {
+        self.readback.copy_from(encoder, &self.texture);
+    }
+}
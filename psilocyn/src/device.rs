@@ -1,15 +1,33 @@
+use hyphae::configuration::graphics::{
+    InternalFormat, PresentationFilter, RenderResolution, Tonemapping,
+};
 use hyphae::configuration::Configuration;
 use hyphae::events::CubensisEvent;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::sync::{Arc, Mutex};
+
+///Format of the offscreen output texture when rendering without a surface
+pub const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+///wgpu only accepts render passes with 1 or 4 samples, whatever the adapter supports
+const SUPPORTED_SAMPLE_COUNTS: &[u32] = &[1, 4];
 
 pub struct GraphicsDevice {
-    surface: RefCell<wgpu::Surface>,
+    surface: RefCell<Option<wgpu::Surface>>,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     surface_configuration: RefCell<wgpu::SurfaceConfiguration>,
     size: RefCell<winit::dpi::PhysicalSize<u32>>,
     pub window_scale_factor: f64,
-    event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>,
+    event_proxy: Option<winit::event_loop::EventLoopProxy<CubensisEvent>>,
+    default_sample_count: u32,
+    internal_format: InternalFormat,
+    pub tonemapping: Tonemapping,
+    render_resolution: RenderResolution,
+    scene_render_resolution: Cell<Option<RenderResolution>>,
+    pub presentation_filter: PresentationFilter,
+    ///Collects wgpu errors while `capture_errors` runs, they are fatal otherwise
+    captured_errors: Arc<Mutex<Option<Vec<String>>>>,
 }
 
 impl GraphicsDevice {
@@ -32,13 +50,175 @@ impl GraphicsDevice {
             .filter(|adapter| surface.get_preferred_format(&adapter).is_some())
             .next()
             .unwrap();
-        let (device, queue) = pollster::block_on(adapter.request_device(
+        let (device, queue) = Self::request_device(&adapter);
+        let captured_errors = Self::install_error_handler(&device);
+        let surface_configuration = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface.get_preferred_format(&adapter).unwrap(),
+            width: size.width,
+            height: size.height,
+            present_mode: if configuration.graphics.enable_vsync {
+                wgpu::PresentMode::Fifo
+            } else {
+                wgpu::PresentMode::Mailbox
+            },
+        };
+        surface.configure(&device, &surface_configuration);
+        let window_scale_factor = window.scale_factor();
+        let surface = RefCell::new(Some(surface));
+        let surface_configuration = RefCell::new(surface_configuration);
+        let size = RefCell::new(size);
+        let default_sample_count = Self::validate_sample_count(
+            SUPPORTED_SAMPLE_COUNTS,
+            configuration.graphics.sample_count,
+        );
+        Self {
+            surface,
+            device,
+            queue,
+            surface_configuration,
+            size,
+            event_proxy: Some(event_proxy),
+            window_scale_factor,
+            default_sample_count,
+            internal_format: configuration.graphics.internal_format,
+            tonemapping: configuration.graphics.tonemapping,
+            render_resolution: configuration.graphics.render_resolution,
+            scene_render_resolution: Cell::new(None),
+            presentation_filter: configuration.graphics.presentation_filter,
+            captured_errors,
+        }
+    }
+
+    ///Creates a device without a window or surface, rendering only into offscreen textures
+    pub fn new_headless(configuration: Configuration, size: winit::dpi::PhysicalSize<u32>) -> Self {
+        log::debug!("Creating new headless GraphicsDevice");
+        let backend_preference = if configuration.graphics.prefer_legacy_backends {
+            wgpu::Backends::SECONDARY
+        } else {
+            wgpu::Backends::PRIMARY
+        };
+        let instance = wgpu::Instance::new(backend_preference);
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: configuration.graphics.force_software_adapter,
+            compatible_surface: None,
+        }))
+        .or_else(|| {
+            log::debug!("No preferred adapter available, falling back to the first enumerated one");
+            instance.enumerate_adapters(backend_preference).next()
+        })
+        .expect("No graphics adapter available for headless rendering");
+        log::info!("Headless rendering on adapter {:?}", adapter.get_info());
+        let (device, queue) = Self::request_device(&adapter);
+        let captured_errors = Self::install_error_handler(&device);
+        let default_sample_count = Self::validate_sample_count(
+            SUPPORTED_SAMPLE_COUNTS,
+            configuration.graphics.sample_count,
+        );
+        let surface_configuration = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: HEADLESS_FORMAT,
+            width: size.width,
+            height: size.height,
+            present_mode: wgpu::PresentMode::Immediate,
+        };
+        Self {
+            surface: RefCell::new(None),
+            device,
+            queue,
+            surface_configuration: RefCell::new(surface_configuration),
+            size: RefCell::new(size),
+            window_scale_factor: 1.0,
+            event_proxy: None,
+            default_sample_count,
+            internal_format: configuration.graphics.internal_format,
+            tonemapping: configuration.graphics.tonemapping,
+            render_resolution: configuration.graphics.render_resolution,
+            scene_render_resolution: Cell::new(None),
+            presentation_filter: configuration.graphics.presentation_filter,
+            captured_errors,
+        }
+    }
+
+    ///Multisample count for a scene rendering into `formats`, `scene_sample_count` overrides the configured one.
+    ///Scenes rendering into a format that can't be multisampled render with a single sample.
+    pub fn sample_count(
+        &self,
+        scene_sample_count: Option<u32>,
+        formats: &[wgpu::TextureFormat],
+    ) -> u32 {
+        let sample_count = match scene_sample_count {
+            Some(sample_count) => {
+                Self::validate_sample_count(SUPPORTED_SAMPLE_COUNTS, sample_count)
+            }
+            None => self.default_sample_count,
+        };
+        if sample_count == 1 {
+            return sample_count;
+        }
+        match formats
+            .iter()
+            .find(|format| !Self::supports_multisampling(**format))
+        {
+            Some(format) => {
+                log::warn!(
+                    "Format {:?} can't be multisampled, rendering with a single sample",
+                    format
+                );
+                1
+            }
+            None => sample_count,
+        }
+    }
+
+    ///Without adapter specific format features every adapter reports the guaranteed ones,
+    ///with which only renderable formats that can be filtered may be multisampled.
+    ///This rules out the 32 bit float formats.
+    fn supports_multisampling(format: wgpu::TextureFormat) -> bool {
+        let features = format.describe().guaranteed_format_features;
+        features.filterable
+            && features
+                .allowed_usages
+                .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+    }
+
+    ///The requested sample count if supported, the nearest supported one otherwise, preferring the lower one
+    fn validate_sample_count(supported_sample_counts: &[u32], sample_count: u32) -> u32 {
+        if supported_sample_counts.contains(&sample_count) {
+            return sample_count;
+        }
+        let fallback = supported_sample_counts
+            .iter()
+            .copied()
+            .min_by_key(|supported| (supported.abs_diff(sample_count), *supported))
+            .unwrap_or(1);
+        log::warn!(
+            "Sample count {} is not supported, using {} instead",
+            sample_count,
+            fallback
+        );
+        fallback
+    }
+
+    fn request_device(adapter: &wgpu::Adapter) -> (wgpu::Device, wgpu::Queue) {
+        log::trace!("Requesting graphics device");
+        let requested_features = wgpu::Features::default()
+            | wgpu::Features::SHADER_FLOAT64
+            | wgpu::Features::TEXTURE_BINDING_ARRAY
+            | wgpu::Features::BUFFER_BINDING_ARRAY
+            | wgpu::Features::VERTEX_WRITABLE_STORAGE;
+        //software adapters don't support every optional feature, so only request what's available
+        let features = requested_features & adapter.features();
+        if features != requested_features {
+            log::warn!(
+                "Adapter is missing requested features: {:?}",
+                requested_features - features
+            );
+        }
+        pollster::block_on(adapter.request_device(
             &wgpu::DeviceDescriptor {
-                features: wgpu::Features::default()
-                    | wgpu::Features::SHADER_FLOAT64
-                    | wgpu::Features::TEXTURE_BINDING_ARRAY
-                    | wgpu::Features::BUFFER_BINDING_ARRAY
-                    | wgpu::Features::VERTEX_WRITABLE_STORAGE,
+                features,
                 //values initialized from defaults
                 limits: wgpu::Limits {
                     max_texture_dimension_1d: 8192,
@@ -66,36 +246,52 @@ impl GraphicsDevice {
             },
             None,
         ))
-        .unwrap();
-        let surface_configuration = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_preferred_format(&adapter).unwrap(),
-            width: size.width,
-            height: size.height,
-            present_mode: if configuration.graphics.enable_vsync {
-                wgpu::PresentMode::Fifo
-            } else {
-                wgpu::PresentMode::Mailbox
-            },
-        };
-        surface.configure(&device, &surface_configuration);
-        let window_scale_factor = window.scale_factor();
-        let surface = RefCell::new(surface);
-        let surface_configuration = RefCell::new(surface_configuration);
-        let size = RefCell::new(size);
-        Self {
-            surface,
-            device,
-            queue,
-            surface_configuration,
-            size,
-            event_proxy,
-            window_scale_factor,
+        .unwrap()
+    }
+
+    fn install_error_handler(device: &wgpu::Device) -> Arc<Mutex<Option<Vec<String>>>> {
+        let captured_errors: Arc<Mutex<Option<Vec<String>>>> = Arc::new(Mutex::new(None));
+        let handler_errors = captured_errors.clone();
+        device.on_uncaptured_error(move |error| match handler_errors.lock().unwrap().as_mut() {
+            Some(errors) => errors.push(error.to_string()),
+            None => panic!("wgpu error: {}", error),
+        });
+        captured_errors
+    }
+
+    ///Runs `create`, failing with the wgpu validation errors it raised instead of panicking on them.
+    ///Objects created by a failing `create` are invalid and must not be used.
+    pub fn capture_errors<T>(&self, create: impl FnOnce() -> T) -> anyhow::Result<T> {
+        *self.captured_errors.lock().unwrap() = Some(Vec::new());
+        let created = create();
+        let errors = self
+            .captured_errors
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_default();
+        if errors.is_empty() {
+            Ok(created)
+        } else {
+            Err(anyhow::Error::msg(errors.join("\n")))
         }
     }
+
+    pub fn is_headless(&self) -> bool {
+        self.surface.borrow().is_none()
+    }
+
+    ///Largest width or height of a 2D texture the device can create
+    pub fn max_texture_dimension(&self) -> u32 {
+        self.device.limits().max_texture_dimension_2d
+    }
+
     pub(crate) fn get_current_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
         log::trace!("Retrieving presentation texture for the current frame");
-        self.surface.borrow().get_current_texture()
+        match self.surface.borrow().as_ref() {
+            Some(surface) => surface.get_current_texture(),
+            None => Err(wgpu::SurfaceError::Lost),
+        }
     }
     pub fn create_extent3d(&self, extent_depth: u32) -> wgpu::Extent3d {
         log::trace!("Creating surface-configured Extent3d");
@@ -106,18 +302,64 @@ impl GraphicsDevice {
             depth_or_array_layers: extent_depth,
         }
     }
+    ///Size of the rendered frame, scaled from the surface by the render resolution
+    pub fn create_render_extent3d(&self, extent_depth: u32) -> wgpu::Extent3d {
+        log::trace!("Creating render resolution Extent3d");
+        let size = self.get_render_size();
+        wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: extent_depth,
+        }
+    }
+    pub fn get_render_size(&self) -> winit::dpi::PhysicalSize<u32> {
+        let configuration = self.surface_configuration.borrow();
+        let resolution = self
+            .scene_render_resolution
+            .get()
+            .unwrap_or(self.render_resolution);
+        let max_dimension = self.max_texture_dimension();
+        let [width, height] = resolution
+            .resolve(configuration.width, configuration.height)
+            .map(|dimension| dimension.min(max_dimension));
+        winit::dpi::PhysicalSize::new(width, height)
+    }
+    ///Overrides the configured render resolution for the current scene,
+    ///true if the render size changed and everything sized by it has to be resized
+    pub fn set_scene_render_resolution(
+        &self,
+        scene_render_resolution: Option<RenderResolution>,
+    ) -> bool {
+        log::debug!(
+            "Setting scene render resolution to {:?}",
+            scene_render_resolution
+        );
+        let previous_size = self.get_render_size();
+        self.scene_render_resolution.set(scene_render_resolution);
+        self.get_render_size() != previous_size
+    }
     pub fn get_format(&self) -> wgpu::TextureFormat {
         log::trace!("Retrieve surface format");
         let configuration = self.surface_configuration.borrow();
         configuration.format
     }
+    ///Format of the rendered frame and render history, tonemapped into the surface format on presentation
+    pub fn get_internal_format(&self) -> wgpu::TextureFormat {
+        match self.internal_format {
+            InternalFormat::Surface => self.get_format(),
+            InternalFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+            InternalFormat::Rgba32Float => wgpu::TextureFormat::Rgba32Float,
+        }
+    }
     pub fn get_size(&self) -> winit::dpi::PhysicalSize<u32> {
         log::trace!("Retrieving window size");
         self.size.clone().into_inner()
     }
     pub(crate) fn get_event_proxy(&self) -> winit::event_loop::EventLoopProxy<CubensisEvent> {
         log::trace!("Cloning device event proxy");
-        self.event_proxy.clone()
+        self.event_proxy
+            .clone()
+            .expect("Headless graphics devices have no event loop")
     }
     pub(crate) fn resize(&self, size: winit::dpi::PhysicalSize<u32>) -> bool {
         log::trace!("Resizing device");
@@ -126,8 +368,9 @@ impl GraphicsDevice {
             let mut surface_configuration = self.surface_configuration.borrow_mut();
             surface_configuration.width = size.width;
             surface_configuration.height = size.height;
-            let surface = self.surface.borrow_mut();
-            surface.configure(&self.device, &surface_configuration);
+            if let Some(surface) = self.surface.borrow_mut().as_ref() {
+                surface.configure(&self.device, &surface_configuration);
+            }
             log::trace!("Resize device");
             return true;
         }
@@ -135,3 +378,22 @@ impl GraphicsDevice {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_sample_counts_are_downgraded() {
+        let validate = |sample_count| {
+            GraphicsDevice::validate_sample_count(SUPPORTED_SAMPLE_COUNTS, sample_count)
+        };
+        assert_eq!(validate(1), 1);
+        assert_eq!(validate(4), 4);
+        assert_eq!(validate(0), 1);
+        assert_eq!(validate(2), 1);
+        assert_eq!(validate(3), 4);
+        assert_eq!(validate(8), 4);
+        assert_eq!(validate(16), 4);
+    }
+}
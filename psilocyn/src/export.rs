@@ -0,0 +1,133 @@
+use crate::headless::HeadlessRenderer;
+use crate::resources::CubensisResourceCollection;
+use hyphae::configuration::Configuration;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub enum ExportTarget {
+    ///Writes numbered PNG files into this directory
+    ImageSequence(PathBuf),
+    ///Pipes raw RGBA frames to the standard input of this command.
+    ///`{width}`, `{height}` and `{fps}` in any argument are replaced with the export settings.
+    Encoder(Vec<String>),
+}
+
+pub struct ExportSettings {
+    pub size: winit::dpi::PhysicalSize<u32>,
+    pub frames_per_second: u32,
+    pub frame_count: u64,
+    pub target: ExportTarget,
+}
+
+///Renders a scene frame by frame at a fixed rate, independent of wall-clock time
+pub struct OfflineExporter<ResourceCollection: CubensisResourceCollection> {
+    renderer: HeadlessRenderer<ResourceCollection>,
+    settings: ExportSettings,
+}
+
+impl<ResourceCollection: CubensisResourceCollection> OfflineExporter<ResourceCollection> {
+    pub fn new(configuration: Configuration, settings: ExportSettings) -> anyhow::Result<Self> {
+        log::debug!("Creating offline exporter");
+        let renderer = HeadlessRenderer::new(configuration, settings.size)?;
+        Ok(Self { renderer, settings })
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        log::info!(
+            "Exporting {} frames at {} frames/second",
+            self.settings.frame_count,
+            self.settings.frames_per_second
+        );
+        let mut sink = FrameSink::new(&self.settings)?;
+        for frame_index in 1..=self.settings.frame_count {
+            self.renderer
+                .render_frame(self.frame_time_delta(frame_index));
+            let frame = self.renderer.read_frame()?;
+            sink.write(frame_index, &frame)?;
+            log::debug!(
+                "Exported frame {}/{}",
+                frame_index,
+                self.settings.frame_count
+            );
+        }
+        sink.finish()
+    }
+
+    ///Exact time step into frame `frame_index`; rounding never accumulates across frames
+    fn frame_time_delta(&self, frame_index: u64) -> Duration {
+        let frame_time = |index: u64| {
+            Duration::from_nanos(
+                (index as u128 * 1_000_000_000 / self.settings.frames_per_second as u128) as u64,
+            )
+        };
+        frame_time(frame_index) - frame_time(frame_index - 1)
+    }
+}
+
+enum FrameSink {
+    ImageSequence(PathBuf),
+    Encoder(std::process::Child),
+}
+
+impl FrameSink {
+    fn new(settings: &ExportSettings) -> anyhow::Result<Self> {
+        match &settings.target {
+            ExportTarget::ImageSequence(directory) => {
+                std::fs::create_dir_all(directory)?;
+                Ok(FrameSink::ImageSequence(directory.clone()))
+            }
+            ExportTarget::Encoder(command) => {
+                let arguments: Vec<String> = command
+                    .iter()
+                    .map(|argument| {
+                        argument
+                            .replace("{width}", &settings.size.width.to_string())
+                            .replace("{height}", &settings.size.height.to_string())
+                            .replace("{fps}", &settings.frames_per_second.to_string())
+                    })
+                    .collect();
+                let (program, arguments) = arguments
+                    .split_first()
+                    .ok_or_else(|| anyhow::Error::msg("Encoder command is empty"))?;
+                log::debug!("Spawning encoder {} {:?}", program, arguments);
+                let child = std::process::Command::new(program)
+                    .args(arguments)
+                    .stdin(std::process::Stdio::piped())
+                    .spawn()?;
+                Ok(FrameSink::Encoder(child))
+            }
+        }
+    }
+
+    fn write(&mut self, frame_index: u64, frame: &image::RgbaImage) -> anyhow::Result<()> {
+        match self {
+            FrameSink::ImageSequence(directory) => {
+                frame.save(directory.join(format!("frame_{:06}.png", frame_index)))?;
+            }
+            FrameSink::Encoder(child) => {
+                child
+                    .stdin
+                    .as_mut()
+                    .ok_or_else(|| anyhow::Error::msg("Encoder standard input is closed"))?
+                    .write_all(frame.as_raw())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> anyhow::Result<()> {
+        if let FrameSink::Encoder(mut child) = self {
+            //closing standard input signals the end of the stream to the encoder
+            drop(child.stdin.take());
+            let status = child.wait()?;
+            if !status.success() {
+                return Err(anyhow::Error::msg(format!(
+                    "Encoder exited with {}",
+                    status
+                )));
+            }
+        }
+        Ok(())
+    }
+}
@@ -72,6 +72,12 @@ impl GuiHost {
     }
 }
 
+///A view the GUI is drawn into, along with its physical size
+pub(crate) struct GuiRenderTarget<'a> {
+    pub view: &'a wgpu::TextureView,
+    pub size: wgpu::Extent3d,
+}
+
 pub(crate) trait CubensisGuiRenderer<ResourceCollection, Gui>
 where
     ResourceCollection: CubensisResourceCollection,
@@ -83,7 +89,7 @@ where
         app: &mut Gui,
         library: &Library,
         resource_collection: &ResourceCollection,
-        render_target: &wgpu::TextureView,
+        render_targets: &[GuiRenderTarget],
     );
 }
 
@@ -98,7 +104,7 @@ where
         app: &mut Gui,
         library: &Library,
         resource_collection: &ResourceCollection,
-        render_target: &wgpu::TextureView,
+        render_targets: &[GuiRenderTarget],
     ) {
         log::trace!("Rendering GUI");
         gui_host.platform.begin_frame();
@@ -146,10 +152,25 @@ where
             &paint_jobs,
             &screen_descriptor,
         );
-        gui_host
-            .gui_renderpass
-            .execute(self, &render_target, &paint_jobs, &screen_descriptor, None)
-            .unwrap();
+        for render_target in render_targets {
+            //scaling physical size and scale factor together keeps the logical layout identical
+            let scale = render_target.size.width as f32 / extent.width as f32;
+            let screen_descriptor = egui_wgpu_backend::ScreenDescriptor {
+                physical_width: render_target.size.width,
+                physical_height: render_target.size.height,
+                scale_factor: gui_host.graphics.window_scale_factor as f32 * scale,
+            };
+            gui_host
+                .gui_renderpass
+                .execute(
+                    self,
+                    render_target.view,
+                    &paint_jobs,
+                    &screen_descriptor,
+                    None,
+                )
+                .unwrap();
+        }
     }
 }
 
@@ -157,7 +178,7 @@ pub trait CubensisGuiApp<ResourceCollection>
 where
     ResourceCollection: CubensisResourceCollection,
 {
-    fn new() -> Self;
+    fn new(event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>) -> Self;
     fn update(&mut self, time_delta: std::time::Duration);
     fn draw(
         &mut self,
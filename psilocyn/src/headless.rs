@@ -0,0 +1,179 @@
+use crate::device::GraphicsDevice;
+use crate::presentation::PresentationPass;
+use crate::readback::TextureReadback;
+use crate::render_graph::RenderGraph;
+use crate::resources::view::ViewRegion;
+use crate::resources::{CubensisRenderGraphSpawner, CubensisResourceCollection};
+use hyphae::configuration::Configuration;
+use hyphae::scene::Scene;
+use std::rc::Rc;
+
+///Renders the current scene into offscreen textures without a window, surface or GUI
+pub struct HeadlessRenderer<ResourceCollection: CubensisResourceCollection> {
+    graphics: Rc<GraphicsDevice>,
+    resource_collection: ResourceCollection,
+    presentation_pass: PresentationPass,
+    render_graph: RenderGraph,
+    scene: Scene,
+    output: OffscreenTarget,
+}
+
+impl<ResourceCollection: CubensisResourceCollection> HeadlessRenderer<ResourceCollection> {
+    pub fn new(
+        configuration: Configuration,
+        size: winit::dpi::PhysicalSize<u32>,
+    ) -> anyhow::Result<Self> {
+        log::debug!(
+            "Creating new headless renderer at {}x{}",
+            size.width,
+            size.height
+        );
+        let graphics = Rc::new(GraphicsDevice::new_headless(configuration.clone(), size));
+        let max_dimension = graphics.max_texture_dimension();
+        if size.width > max_dimension || size.height > max_dimension {
+            return Err(anyhow::Error::msg(format!(
+                "Size {}x{} exceeds the device's largest texture dimension of {}",
+                size.width, size.height, max_dimension
+            )));
+        }
+        let scene = configuration
+            .library
+            .build_library()
+            .current_scene()
+            .clone();
+        graphics.set_scene_render_resolution(scene.render_resolution);
+        let resource_collection = ResourceCollection::new(graphics.clone(), configuration.clone())?;
+        let presentation_pass =
+            PresentationPass::new(graphics.clone(), scene.history_depth, scene.expose_depth);
+        let render_graph = scene.create_render_graph(
+            graphics.clone(),
+            &resource_collection,
+            presentation_pass.get_bind_group_layout(),
+        )?;
+        let output = OffscreenTarget::new(&graphics);
+        Ok(Self {
+            graphics,
+            resource_collection,
+            presentation_pass,
+            render_graph,
+            scene,
+            output,
+        })
+    }
+
+    pub fn graphics(&self) -> &Rc<GraphicsDevice> {
+        &self.graphics
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    pub fn resource_collection(&self) -> &ResourceCollection {
+        &self.resource_collection
+    }
+
+    pub fn resource_collection_mut(&mut self) -> &mut ResourceCollection {
+        &mut self.resource_collection
+    }
+
+    ///Advances all resources by `time_delta` and renders a single frame into the output texture
+    pub fn render_frame(&mut self, time_delta: std::time::Duration) {
+        self.advance(time_delta);
+        self.draw_frame();
+    }
+
+    ///Advances all resources by `time_delta` without rendering
+    pub fn advance(&mut self, time_delta: std::time::Duration) {
+        log::trace!("Advancing headless renderer");
+        self.resource_collection.update(time_delta);
+        self.render_graph.update(time_delta);
+    }
+
+    ///Renders a frame into the output texture with resources frozen in their current state
+    pub fn draw_frame(&mut self) {
+        log::trace!("Rendering headless frame");
+        self.presentation_pass.start_frame();
+        let mut encoder =
+            self.graphics
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Headless Render Encoder"),
+                });
+        let bind_groups = self.resource_collection.get_bind_groups();
+        self.render_graph.encode(
+            &mut encoder,
+            bind_groups.as_slice(),
+            self.presentation_pass.get_current_bind_group(),
+            self.presentation_pass.create_presentation_view(),
+            self.presentation_pass.get_depth_history(),
+        );
+        self.presentation_pass.store_history(&mut encoder);
+        self.render_graph.encode_post_processing(
+            &mut encoder,
+            bind_groups.as_slice(),
+            self.presentation_pass.get_output_texture(),
+            self.presentation_pass.create_presentation_view(),
+        );
+        self.presentation_pass
+            .encode_presentation(&mut encoder, &self.output.texture_view);
+        self.output
+            .readback
+            .copy_from(&mut encoder, &self.output.texture);
+        self.graphics
+            .queue
+            .submit(std::iter::once(encoder.finish()));
+    }
+
+    ///Waits for the last rendered frame and copies it into CPU memory
+    pub fn read_frame(&self) -> anyhow::Result<image::RgbaImage> {
+        log::trace!("Reading headless frame");
+        self.output.readback.read(&self.graphics.device)
+    }
+
+    ///Renders only `region` of a larger image into the output texture until the next resize
+    pub fn set_view_region(&mut self, region: &ViewRegion) {
+        log::trace!("Setting headless view region to {:?}", region);
+        self.resource_collection.set_view_region(region);
+    }
+
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        log::trace!("Resizing headless renderer");
+        if self.graphics.resize(size) {
+            self.resource_collection.resize();
+            self.presentation_pass.resize();
+            self.render_graph.resize();
+            self.output = OffscreenTarget::new(&self.graphics);
+        }
+    }
+}
+
+struct OffscreenTarget {
+    texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+    readback: TextureReadback,
+}
+
+impl OffscreenTarget {
+    fn new(graphics: &GraphicsDevice) -> Self {
+        log::trace!("Creating offscreen output target");
+        let size = graphics.create_extent3d(1);
+        let format = graphics.get_format();
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Output Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let readback = TextureReadback::new(&graphics.device, size, format);
+        Self {
+            texture,
+            texture_view,
+            readback,
+        }
+    }
+}
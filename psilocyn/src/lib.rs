@@ -1,9 +1,17 @@
+pub mod capture;
 pub mod device;
+pub mod export;
 pub mod gui;
+pub mod headless;
 pub mod mesh;
+pub mod post;
+pub mod preprocessor;
 pub mod presentation;
+pub mod readback;
+pub mod render_graph;
 pub mod renderer;
 pub mod resources;
+pub mod tiled;
 pub mod validation;
 pub mod window;
 pub const DEPTH_BUFFER_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
@@ -1,45 +1,207 @@
-use hyphae::scene::geometry::GeometrySource;
+use crate::mesh::geometry::Geometry;
+use hyphae::scene::geometry::{GeometrySource, ParticleRendering, ParticleSystem};
+use hyphae::scene::instances::InstanceDescriptor;
 use hyphae::scene::primitives::PrimitiveType;
+use hyphae::scene::shaders::ComputeShader;
 use wgpu::util::DeviceExt;
 
+///Vertex attributes at shader locations 0 (position), 1 (uv), 2 (normal), 3 (colour) and 4 (tangent)
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Vertex {
-    position: [f32; 3],
-    uv: [f32; 2],
+    pub(crate) position: [f32; 3],
+    pub(crate) uv: [f32; 2],
+    pub(crate) normal: [f32; 3],
+    pub(crate) color: [f32; 4],
+    ///`w` is the handedness of the bitangent `cross(normal, tangent.xyz) * w`
+    pub(crate) tangent: [f32; 4],
 }
+//in field order, so the offsets match the struct layout
+const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+    0 => Float32x3,
+    1 => Float32x2,
+    2 => Float32x3,
+    3 => Float32x4,
+    4 => Float32x4,
+];
 impl Vertex {
+    ///A white vertex without normal and tangent
+    pub const fn new(position: [f32; 3], uv: [f32; 2]) -> Self {
+        Self {
+            position,
+            uv,
+            normal: [0.0; 3],
+            color: [1.0; 4],
+            tangent: [0.0; 4],
+        }
+    }
+
+    pub const fn with_normal(mut self, normal: [f32; 3]) -> Self {
+        self.normal = normal;
+        self
+    }
+
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         log::trace!("Retrieving Vertex descriptor");
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x2,
-                },
-            ],
+            attributes: &VERTEX_ATTRIBUTES,
+        }
+    }
+}
+
+///Per instance attributes at shader locations 5 (`offset` and `scale` as `w`), 6 (colour) and 7 (data)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Instance {
+    offset: [f32; 3],
+    scale: f32,
+    color: [f32; 4],
+    data: [f32; 4],
+}
+const INSTANCE_ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![
+    5 => Float32x4,
+    6 => Float32x4,
+    7 => Float32x4,
+];
+impl Instance {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        log::trace!("Retrieving Instance descriptor");
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &INSTANCE_ATTRIBUTES,
+        }
+    }
+}
+
+impl From<&InstanceDescriptor> for Instance {
+    fn from(descriptor: &InstanceDescriptor) -> Self {
+        Self {
+            offset: descriptor.offset,
+            scale: descriptor.scale,
+            color: descriptor.color,
+            data: descriptor.data,
+        }
+    }
+}
+
+///State of a single particle, laid out like the WGSL `Particle` struct of the particle shaders
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Particle {
+    position: [f32; 3],
+    size: f32,
+    velocity: [f32; 3],
+    age: f32,
+    color: [f32; 4],
+    lifetime: f32,
+    alive: u32,
+    seed: u32,
+    padding: u32,
+}
+const PARTICLE_ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+    8 => Float32x3,
+    9 => Float32,
+    10 => Float32x3,
+    11 => Float32,
+    12 => Float32x4,
+    13 => Float32,
+    14 => Uint32,
+];
+impl Particle {
+    ///Per instance attributes at locations 8 to 14, leaving the lower locations to vertex attributes
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        log::trace!("Retrieving Particle descriptor");
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Particle>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &PARTICLE_ATTRIBUTES,
         }
     }
 }
+
 pub struct MeshBuffers {
     pub(crate) vertex_buffer: wgpu::Buffer,
     pub(crate) index_buffer: wgpu::Buffer,
     pub(crate) indirect_buffer: wgpu::Buffer,
     pub(crate) index_count: u32,
+    pub(crate) index_format: wgpu::IndexFormat,
 }
 impl MeshBuffers {
-    pub fn new(device: &wgpu::Device, geometry_source: &GeometrySource) -> Self {
+    ///`instance_count` initialises the indirect draw arguments of compute generated geometry
+    pub fn new(
+        device: &wgpu::Device,
+        geometry_source: &GeometrySource,
+        instance_count: u32,
+    ) -> anyhow::Result<Self> {
         log::debug!("Creating mesh buffers");
-        let vertices = get_vertices(geometry_source);
-        let indices = get_indices(geometry_source);
+        match geometry_source {
+            GeometrySource::Primitive(primitive) => Ok(Self::from_primitive(device, primitive)),
+            GeometrySource::ComputeShader(compute_shader) => {
+                Self::for_compute_shader(device, compute_shader, instance_count)
+            }
+            GeometrySource::ParticleSystem(particle_system) => {
+                Ok(Self::for_particle_system(device, particle_system))
+            }
+            GeometrySource::File { path, format } => Ok(Self::from_geometry(
+                device,
+                &Geometry::from_file(path, *format)?,
+            )),
+        }
+    }
+
+    pub fn from_primitive(device: &wgpu::Device, primitive: &PrimitiveType) -> Self {
+        log::debug!("Creating mesh buffers for primitive {:?}", primitive);
+        Self::from_geometry(device, &Geometry::from_primitive(primitive))
+    }
+
+    ///Uploads `geometry`, with 16 bit indices if every vertex can be addressed by one
+    pub fn from_geometry(device: &wgpu::Device, geometry: &Geometry) -> Self {
+        if geometry.vertices.len() <= u16::MAX as usize + 1 {
+            let indices: Vec<u16> = geometry.indices.iter().map(|index| *index as u16).collect();
+            Self::from_slices(
+                device,
+                geometry.vertices.as_slice(),
+                indices.as_slice(),
+                wgpu::IndexFormat::Uint16,
+            )
+        } else {
+            Self::from_slices(
+                device,
+                geometry.vertices.as_slice(),
+                geometry.indices.as_slice(),
+                wgpu::IndexFormat::Uint32,
+            )
+        }
+    }
+
+    ///The geometry drawn for every particle: a quad for billboards, a single vertex for points
+    fn for_particle_system(device: &wgpu::Device, particle_system: &ParticleSystem) -> Self {
+        log::debug!(
+            "Creating particle mesh buffers for {}",
+            particle_system.name
+        );
+        match particle_system.rendering {
+            ParticleRendering::Billboards => Self::from_primitive(device, &PrimitiveType::Quad),
+            ParticleRendering::Points => Self::from_geometry(
+                device,
+                &Geometry {
+                    vertices: vec![Vertex::new([0.0, 0.0, 0.0], [0.5, 0.5])],
+                    indices: vec![0],
+                },
+            ),
+        }
+    }
+
+    ///`index_format` has to match the type of `indices`
+    fn from_slices<Index: bytemuck::Pod>(
+        device: &wgpu::Device,
+        vertices: &[Vertex],
+        indices: &[Index],
+        index_format: wgpu::IndexFormat,
+    ) -> Self {
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Vertex Buffer"),
             contents: bytemuck::cast_slice(vertices),
@@ -50,79 +212,88 @@ impl MeshBuffers {
             contents: bytemuck::cast_slice(indices),
             usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::STORAGE,
         });
-        let indirect_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Indirect Buffer"),
-            contents: bytemuck::cast_slice(
-                vec![DrawIndirect {
-                    vertex_count: indices.len() as u32,
-                    instance_count: 1,
-                    base_vertex: 0,
-                    base_instance: 0,
-                }]
-                .as_slice(),
-            ),
-            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE,
-        });
         let index_count = indices.len() as u32;
+        let indirect_buffer = Self::create_indirect_buffer(device, index_count, 1);
         Self {
             vertex_buffer,
             index_buffer,
             indirect_buffer,
             index_count,
+            index_format,
         }
     }
-}
 
-fn get_vertices(geometry: &GeometrySource) -> &[Vertex] {
-    log::trace!("Retrieving vertices");
-    match geometry {
-        GeometrySource::Primitive(primitive) => match primitive {
-            PrimitiveType::Quad => QUAD_VERTICES,
-        },
-        GeometrySource::ComputeShader(_) => QUAD_VERTICES,
+    ///Empty storage buffers filled by the compute shader, which also writes the indirect draw arguments
+    fn for_compute_shader(
+        device: &wgpu::Device,
+        compute_shader: &ComputeShader,
+        instance_count: u32,
+    ) -> anyhow::Result<Self> {
+        log::debug!("Creating storage mesh buffers for {}", compute_shader.name);
+        let vertex_buffer_size =
+            compute_shader.max_vertices as u64 * std::mem::size_of::<Vertex>() as u64;
+        let index_buffer_size = compute_shader.max_indices as u64 * 4;
+        let max_size = device.limits().max_storage_buffer_binding_size as u64;
+        if vertex_buffer_size == 0 || index_buffer_size == 0 {
+            return Err(anyhow::Error::msg(format!(
+                "Compute shader {} needs room for at least one vertex and index",
+                compute_shader.name
+            )));
+        }
+        if vertex_buffer_size > max_size || index_buffer_size > max_size {
+            return Err(anyhow::Error::msg(format!(
+                "Geometry buffers of compute shader {} exceed the {} byte storage buffer limit",
+                compute_shader.name, max_size
+            )));
+        }
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Vertex Storage Buffer"),
+            size: vertex_buffer_size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Index Storage Buffer"),
+            size: index_buffer_size,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let indirect_buffer = Self::create_indirect_buffer(device, 0, instance_count);
+        Ok(Self {
+            vertex_buffer,
+            index_buffer,
+            indirect_buffer,
+            index_count: compute_shader.max_indices,
+            index_format: wgpu::IndexFormat::Uint32,
+        })
     }
-}
 
-fn get_indices(geometry: &GeometrySource) -> &[u16] {
-    log::trace!("Retrieving indices");
-    match geometry {
-        GeometrySource::Primitive(primitive) => match primitive {
-            PrimitiveType::Quad => QUAD_INDICES,
-        },
-        GeometrySource::ComputeShader(_) => QUAD_INDICES,
+    fn create_indirect_buffer(
+        device: &wgpu::Device,
+        index_count: u32,
+        instance_count: u32,
+    ) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Indirect Buffer"),
+            contents: bytemuck::cast_slice(&[DrawIndexedIndirect {
+                index_count,
+                instance_count,
+                base_index: 0,
+                vertex_offset: 0,
+                base_instance: 0,
+            }]),
+            usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE,
+        })
     }
 }
 
-pub const QUAD_VERTICES: &[Vertex] = &[
-    Vertex {
-        position: [-1.0, 1.0, 0.0],
-        uv: [0.0, 1.0],
-    },
-    Vertex {
-        position: [1.0, -1.0, 0.0],
-        uv: [1.0, 0.0],
-    },
-    Vertex {
-        position: [-1.0, -1.0, 0.0],
-        uv: [0.0, 0.0],
-    },
-    Vertex {
-        position: [1.0, 1.0, 0.0],
-        uv: [1.0, 1.0],
-    },
-];
-
-#[rustfmt::skip]
-pub const QUAD_INDICES: &[u16] = &[
-    0, 1, 2,
-    0, 3, 1,
-];
-
+///Arguments of `draw_indexed_indirect`, written by geometry compute shaders
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-struct DrawIndirect {
-    vertex_count: u32,   // The number of vertices to draw.
+struct DrawIndexedIndirect {
+    index_count: u32,    // The number of indices to draw.
     instance_count: u32, // The number of instances to draw.
-    base_vertex: u32,    // The Index of the first vertex to draw.
+    base_index: u32,     // The first index within the index buffer.
+    vertex_offset: i32,  // The value added to each index before reading a vertex.
     base_instance: u32,  // The instance ID of the first instance to draw.
 }
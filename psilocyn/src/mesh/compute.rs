@@ -0,0 +1,172 @@
+use crate::device::GraphicsDevice;
+use crate::mesh::buffers::MeshBuffers;
+use crate::mesh::Mesh;
+use crate::preprocessor::ShaderPreprocessor;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::scene::shaders::ComputeShader;
+
+///Compute pipeline writing a mesh's vertex, index and indirect buffers.
+///The storage buffers are bound after the resource groups at bindings 0 (vertices), 1 (indices) and 2 (indirect arguments).
+pub(crate) struct ComputeGeometry {
+    pub(crate) compute_shader: ComputeShader,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    ///`None` while the compute shader fails to build, which leaves the geometry empty
+    pipeline: Option<wgpu::ComputePipeline>,
+}
+
+impl ComputeGeometry {
+    ///A compute shader failing to build has its error pushed to `build_errors`
+    pub fn new(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        compute_shader: ComputeShader,
+        buffers: &MeshBuffers,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        build_errors: &mut Vec<ShaderDiagnostic>,
+    ) -> Self {
+        log::debug!("Creating compute geometry {}", compute_shader.name);
+        let bind_group_layout = Self::create_bind_group_layout(&graphics.device);
+        let bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Compute geometry bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffers.vertex_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: buffers.index_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: buffers.indirect_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+        let pipeline = Self::create_pipeline(
+            graphics,
+            preprocessor,
+            &compute_shader,
+            bind_group_layouts,
+            &bind_group_layout,
+        )
+        .map_err(|error| {
+            let diagnostic = ShaderDiagnostic::from_error(&compute_shader.path, &error);
+            log::warn!("Skipping failed geometry compute shader: {}", diagnostic);
+            build_errors.push(diagnostic);
+        })
+        .ok();
+        Self {
+            compute_shader,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+        }
+    }
+
+    ///Recompiles the compute shader, keeping the buffers and their contents
+    pub fn rebuild(
+        &mut self,
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> anyhow::Result<()> {
+        log::debug!("Rebuilding compute geometry {}", self.compute_shader.name);
+        self.pipeline = Some(Self::create_pipeline(
+            graphics,
+            preprocessor,
+            &self.compute_shader,
+            bind_group_layouts,
+            &self.bind_group_layout,
+        )?);
+        Ok(())
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Compute geometry bind group layout"),
+            entries: &[storage_entry(0), storage_entry(1), storage_entry(2)],
+        })
+    }
+
+    fn create_pipeline(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        compute_shader: &ComputeShader,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        geometry_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<wgpu::ComputePipeline> {
+        let source = preprocessor.compile(
+            &compute_shader.path,
+            compute_shader.get_language(),
+            naga::ShaderStage::Compute,
+        )?;
+        log::trace!("Creating compute pipeline");
+        let mut pipeline_bind_group_layouts = bind_group_layouts.to_vec();
+        pipeline_bind_group_layouts.push(geometry_bind_group_layout);
+        let pipeline_layout =
+            graphics
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Compute Pipeline Layout"),
+                    bind_group_layouts: pipeline_bind_group_layouts.as_slice(),
+                    push_constant_ranges: &[],
+                });
+        let module = graphics
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Compute Shader Module"),
+                source,
+            });
+        Ok(graphics
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Compute Geometry Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: "main",
+            }))
+    }
+}
+
+pub trait CubensisMeshComputePass<'a> {
+    ///Runs the geometry compute shader of the mesh, if it has one
+    fn generate_mesh_geometry(&mut self, mesh: &'a Mesh, bind_groups: &'a [&wgpu::BindGroup]);
+}
+
+impl<'a> CubensisMeshComputePass<'a> for wgpu::ComputePass<'a> {
+    fn generate_mesh_geometry(&mut self, mesh: &'a Mesh, bind_groups: &'a [&wgpu::BindGroup]) {
+        if let Some(ComputeGeometry {
+            compute_shader,
+            bind_group: geometry_bind_group,
+            pipeline: Some(pipeline),
+            ..
+        }) = &mesh.compute_geometry
+        {
+            log::trace!("Generating mesh geometry");
+            self.set_pipeline(pipeline);
+            let mut bind_group_index = 0;
+            for bind_group in bind_groups {
+                self.set_bind_group(bind_group_index, bind_group, &[]);
+                bind_group_index += 1;
+            }
+            self.set_bind_group(bind_group_index, geometry_bind_group, &[]);
+            let [x, y, z] = compute_shader.workgroups;
+            self.dispatch(x, y, z);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use crate::mesh::buffers::Vertex;
+use crate::mesh::geometry::{normalize, Geometry};
+use hyphae::configuration::library::LibraryConfiguration;
+use hyphae::scene::geometry::MeshFileFormat;
+use std::path::Path;
+
+impl Geometry {
+    ///Loads the triangles of the mesh file at `path`, relative to the scene library.
+    ///Missing normals and tangents are computed.
+    pub fn from_file(path: &str, format: Option<MeshFileFormat>) -> anyhow::Result<Self> {
+        let path = LibraryConfiguration::scene_library_path().join(path);
+        log::debug!("Loading mesh file {:?}", path);
+        let format = format
+            .or_else(|| MeshFileFormat::from_path(&path))
+            .ok_or_else(|| anyhow::Error::msg(format!("Unknown mesh file format of {:?}", path)))?;
+        let mut geometry = match format {
+            MeshFileFormat::Obj => Self::load_obj(&path)?,
+            MeshFileFormat::Gltf => Self::load_gltf(&path)?,
+        };
+        if geometry.indices.is_empty() {
+            return Err(anyhow::Error::msg(format!(
+                "Mesh file {:?} contains no triangles",
+                path
+            )));
+        }
+        geometry.flip_winding();
+        Ok(geometry)
+    }
+
+    fn load_obj(path: &Path) -> anyhow::Result<Self> {
+        let (models, _materials) = tobj::load_obj(
+            path,
+            &tobj::LoadOptions {
+                single_index: true,
+                triangulate: true,
+                ignore_points: true,
+                ignore_lines: true,
+            },
+        )?;
+        let mut geometry = Self::empty();
+        for model in models {
+            log::trace!("Loading OBJ model {}", model.name);
+            let mesh = model.mesh;
+            let vertex_count = mesh.positions.len() / 3;
+            let has_normals = mesh.normals.len() == mesh.positions.len();
+            let first_vertex = geometry.vertices.len() as u32;
+            geometry.vertices.extend((0..vertex_count).map(|i| {
+                let mut vertex = Vertex::new(
+                    [
+                        mesh.positions[3 * i],
+                        mesh.positions[3 * i + 1],
+                        mesh.positions[3 * i + 2],
+                    ],
+                    match mesh.texcoords.get(2 * i..2 * i + 2) {
+                        Some(uv) => [uv[0], uv[1]],
+                        None => [0.0, 0.0],
+                    },
+                );
+                if has_normals {
+                    vertex.normal = [
+                        mesh.normals[3 * i],
+                        mesh.normals[3 * i + 1],
+                        mesh.normals[3 * i + 2],
+                    ];
+                }
+                if let Some(color) = mesh.vertex_color.get(3 * i..3 * i + 3) {
+                    vertex.color = [color[0], color[1], color[2], 1.0];
+                }
+                vertex
+            }));
+            let first_index = geometry.indices.len();
+            geometry
+                .indices
+                .extend(mesh.indices.iter().map(|index| first_vertex + index));
+            if !has_normals {
+                geometry.compute_normals(first_index);
+            }
+            geometry.compute_tangents(first_index);
+        }
+        Ok(geometry)
+    }
+
+    fn load_gltf(path: &Path) -> anyhow::Result<Self> {
+        let (document, buffers, _images) = gltf::import(path)?;
+        let mut geometry = Self::empty();
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+        match scene {
+            Some(scene) => {
+                for node in scene.nodes() {
+                    geometry.load_gltf_node(&node, IDENTITY, &buffers);
+                }
+            }
+            //files without scenes still carry meshes
+            None => {
+                for mesh in document.meshes() {
+                    geometry.load_gltf_mesh(&mesh, IDENTITY, &buffers);
+                }
+            }
+        }
+        Ok(geometry)
+    }
+
+    fn load_gltf_node(
+        &mut self,
+        node: &gltf::Node,
+        parent_transform: [[f32; 4]; 4],
+        buffers: &[gltf::buffer::Data],
+    ) {
+        let transform = multiply(parent_transform, node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            self.load_gltf_mesh(&mesh, transform, buffers);
+        }
+        for child in node.children() {
+            self.load_gltf_node(&child, transform, buffers);
+        }
+    }
+
+    fn load_gltf_mesh(
+        &mut self,
+        mesh: &gltf::Mesh,
+        transform: [[f32; 4]; 4],
+        buffers: &[gltf::buffer::Data],
+    ) {
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                log::warn!(
+                    "Skipping glTF primitive of mesh {:?} with mode {:?}",
+                    mesh.name(),
+                    primitive.mode()
+                );
+                continue;
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let positions = match reader.read_positions() {
+                Some(positions) => positions,
+                None => continue,
+            };
+            let first_vertex = self.vertices.len() as u32;
+            //glTF places the UV origin at the top left, the primitives at the bottom left
+            self.vertices.extend(
+                positions
+                    .map(|position| Vertex::new(transform_point(transform, position), [0.0, 1.0])),
+            );
+            let vertices = &mut self.vertices[first_vertex as usize..];
+            if let Some(tex_coords) = reader.read_tex_coords(0) {
+                for (vertex, [u, v]) in vertices.iter_mut().zip(tex_coords.into_f32()) {
+                    vertex.uv = [u, 1.0 - v];
+                }
+            }
+            let has_normals = match reader.read_normals() {
+                Some(normals) => {
+                    for (vertex, normal) in vertices.iter_mut().zip(normals) {
+                        vertex.normal = transform_normal(transform, normal);
+                    }
+                    true
+                }
+                None => false,
+            };
+            if let Some(colors) = reader.read_colors(0) {
+                for (vertex, color) in vertices.iter_mut().zip(colors.into_rgba_f32()) {
+                    vertex.color = color;
+                }
+            }
+            let first_index = self.indices.len();
+            match reader.read_indices() {
+                Some(indices) => self
+                    .indices
+                    .extend(indices.into_u32().map(|index| first_vertex + index)),
+                None => self
+                    .indices
+                    .extend(first_vertex..self.vertices.len() as u32),
+            }
+            if !has_normals {
+                self.compute_normals(first_index);
+            }
+            match reader.read_tangents() {
+                Some(tangents) => {
+                    let vertices = &mut self.vertices[first_vertex as usize..];
+                    for (vertex, [x, y, z, w]) in vertices.iter_mut().zip(tangents) {
+                        let [x, y, z] = transform_normal(transform, [x, y, z]);
+                        //flipping v flips the bitangent
+                        vertex.tangent = [x, y, z, -w];
+                    }
+                }
+                None => self.compute_tangents(first_index),
+            }
+        }
+    }
+}
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+///Product of two column-major matrices
+fn multiply(a: [[f32; 4]; 4], b: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut product = [[0.0; 4]; 4];
+    for (column, b_column) in b.iter().enumerate() {
+        for row in 0..4 {
+            product[column][row] = (0..4).map(|k| a[k][row] * b_column[k]).sum();
+        }
+    }
+    product
+}
+
+fn transform_point(transform: [[f32; 4]; 4], point: [f32; 3]) -> [f32; 3] {
+    [0, 1, 2]
+        .map(|row| (0..3).map(|k| transform[k][row] * point[k]).sum::<f32>() + transform[3][row])
+}
+
+///Rotates a normal, exact for transforms without non-uniform scale
+fn transform_normal(transform: [[f32; 4]; 4], normal: [f32; 3]) -> [f32; 3] {
+    let rotated = [0, 1, 2].map(|row| (0..3).map(|k| transform[k][row] * normal[k]).sum());
+    normalize(rotated)
+}
@@ -0,0 +1,127 @@
+use crate::mesh::buffers::Vertex;
+
+///Vertices and `u32` indices built on the CPU from a primitive or mesh file, before they are uploaded.
+///Triangles are wound clockwise seen from the front; line primitives are pairs of indices.
+pub struct Geometry {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Geometry {
+    pub fn empty() -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    ///Turns counter-clockwise triangles, as generated or loaded, into the clockwise front faces the pipelines draw
+    pub fn flip_winding(&mut self) {
+        for triangle in self.indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+    }
+
+    ///Smooth, area weighted normals for the counter-clockwise triangles starting at `first_index`
+    pub fn compute_normals(&mut self, first_index: usize) {
+        log::trace!("Computing vertex normals");
+        let triangles = &self.indices[first_index..];
+        for index in triangles {
+            self.vertices[*index as usize].normal = [0.0; 3];
+        }
+        for triangle in triangles.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize].position);
+            let face_normal = cross(subtract(b, a), subtract(c, a));
+            for index in triangle {
+                let vertex = &mut self.vertices[*index as usize];
+                vertex.normal = add(vertex.normal, face_normal);
+            }
+        }
+        for index in triangles {
+            let vertex = &mut self.vertices[*index as usize];
+            vertex.normal = normalize(vertex.normal);
+        }
+    }
+
+    ///Tangents along increasing `u` for the triangles starting at `first_index`, orthogonal to the normals.
+    ///The `w` component is the handedness, the bitangent is `cross(normal, tangent.xyz) * tangent.w`.
+    pub fn compute_tangents(&mut self, first_index: usize) {
+        log::trace!("Computing vertex tangents");
+        let triangles = &self.indices[first_index..];
+        let mut tangents = vec![[0.0; 3]; self.vertices.len()];
+        let mut bitangents = vec![[0.0; 3]; self.vertices.len()];
+        for triangle in triangles.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize]);
+            let (edge_1, edge_2) = (
+                subtract(b.position, a.position),
+                subtract(c.position, a.position),
+            );
+            let (du_1, dv_1) = (b.uv[0] - a.uv[0], b.uv[1] - a.uv[1]);
+            let (du_2, dv_2) = (c.uv[0] - a.uv[0], c.uv[1] - a.uv[1]);
+            let determinant = du_1 * dv_2 - du_2 * dv_1;
+            if determinant.abs() <= f32::EPSILON {
+                continue;
+            }
+            let tangent = scale(
+                subtract(scale(edge_1, dv_2), scale(edge_2, dv_1)),
+                1.0 / determinant,
+            );
+            let bitangent = scale(
+                subtract(scale(edge_2, du_1), scale(edge_1, du_2)),
+                1.0 / determinant,
+            );
+            for index in triangle {
+                tangents[*index as usize] = add(tangents[*index as usize], tangent);
+                bitangents[*index as usize] = add(bitangents[*index as usize], bitangent);
+            }
+        }
+        for index in triangles {
+            let index = *index as usize;
+            let vertex = &mut self.vertices[index];
+            let normal = vertex.normal;
+            let tangent = normalize(subtract(
+                tangents[index],
+                scale(normal, dot(normal, tangents[index])),
+            ));
+            let handedness = if dot(cross(normal, tangent), bitangents[index]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            vertex.tangent = [tangent[0], tangent[1], tangent[2], handedness];
+        }
+    }
+}
+
+pub(crate) fn normalize(vector: [f32; 3]) -> [f32; 3] {
+    let length = dot(vector, vector).sqrt();
+    if length > 0.0 {
+        scale(vector, 1.0 / length)
+    } else {
+        vector
+    }
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn add(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn subtract(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn scale(a: [f32; 3], factor: f32) -> [f32; 3] {
+    [a[0] * factor, a[1] * factor, a[2] * factor]
+}
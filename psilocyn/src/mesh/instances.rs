@@ -0,0 +1,290 @@
+use crate::device::GraphicsDevice;
+use crate::mesh::buffers::Instance;
+use crate::mesh::Mesh;
+use crate::preprocessor::ShaderPreprocessor;
+use crate::validation::CubensisValidatedShader;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::scene::instances::{InstanceDescriptor, InstanceSource};
+use hyphae::scene::shaders::ShaderLanguage;
+use wgpu::util::DeviceExt;
+
+///Workgroup size instance compute shaders have to declare
+pub const INSTANCE_WORKGROUP_SIZE: u32 = 64;
+
+///Per instance attribute buffer of a mesh, bound as vertex buffer 1
+pub(crate) struct MeshInstances {
+    pub(crate) instance_buffer: wgpu::Buffer,
+    pub(crate) count: u32,
+    pub(crate) compute_instances: Option<ComputeInstances>,
+}
+
+impl MeshInstances {
+    ///An instance compute shader failing to build has its error pushed to `build_errors`
+    pub fn new(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        count: u32,
+        source: Option<&InstanceSource>,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        build_errors: &mut Vec<ShaderDiagnostic>,
+    ) -> anyhow::Result<Self> {
+        log::debug!("Creating {} mesh instances", count);
+        let size = count as u64 * std::mem::size_of::<Instance>() as u64;
+        let max_size = graphics.device.limits().max_storage_buffer_binding_size as u64;
+        if size == 0 || size > max_size {
+            return Err(anyhow::Error::msg(format!(
+                "Instance count {} must be at least 1 and fit the {} byte storage buffer limit",
+                count, max_size
+            )));
+        }
+        let mut instances = vec![Instance::from(&InstanceDescriptor::default()); count as usize];
+        if let Some(InstanceSource::Static(descriptors)) = source {
+            if descriptors.len() > count as usize {
+                log::warn!(
+                    "Ignoring {} static instances beyond the instance count {}",
+                    descriptors.len() - count as usize,
+                    count
+                );
+            }
+            for (instance, descriptor) in instances.iter_mut().zip(descriptors) {
+                *instance = Instance::from(descriptor);
+            }
+        }
+        let instance_buffer =
+            graphics
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Instance Buffer"),
+                    contents: bytemuck::cast_slice(instances.as_slice()),
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+                });
+        let compute_instances = match source {
+            Some(source @ (InstanceSource::ComputeShader { .. } | InstanceSource::Spectrum)) => {
+                Some(ComputeInstances::new(
+                    graphics,
+                    preprocessor,
+                    source.clone(),
+                    &instance_buffer,
+                    count,
+                    bind_group_layouts,
+                    build_errors,
+                ))
+            }
+            _ => None,
+        };
+        Ok(Self {
+            instance_buffer,
+            count,
+            compute_instances,
+        })
+    }
+}
+
+///Compute pipeline writing the instance buffer every frame.
+///Bound after the resource groups at bindings 0 (instances) and 1 (`InstanceInfo` uniform).
+pub(crate) struct ComputeInstances {
+    pub(crate) source: InstanceSource,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    ///`None` while the compute shader fails to build, which keeps the initial instances
+    pipeline: Option<wgpu::ComputePipeline>,
+    count: u32,
+}
+
+impl ComputeInstances {
+    fn new(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        source: InstanceSource,
+        instance_buffer: &wgpu::Buffer,
+        count: u32,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        build_errors: &mut Vec<ShaderDiagnostic>,
+    ) -> Self {
+        log::debug!("Creating compute instances {:?}", source);
+        let info_buffer = graphics
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Instance Info Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[InstanceInfo {
+                    count,
+                    padding: [0; 3],
+                }]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group_layout = Self::create_bind_group_layout(&graphics.device);
+        let bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Compute instances bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: instance_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: info_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+        let pipeline = Self::create_pipeline(
+            graphics,
+            preprocessor,
+            &source,
+            bind_group_layouts,
+            &bind_group_layout,
+        )
+        .map_err(|error| {
+            //the built-in spectrum shader has no path of its own
+            let shader_path = source
+                .compute_shader_path()
+                .unwrap_or("instances_spectrum.wgsl");
+            let diagnostic = ShaderDiagnostic::from_error(shader_path, &error);
+            log::warn!("Skipping failed instance compute shader: {}", diagnostic);
+            build_errors.push(diagnostic);
+        })
+        .ok();
+        Self {
+            source,
+            bind_group_layout,
+            bind_group,
+            pipeline,
+            count,
+        }
+    }
+
+    ///Recompiles the instance compute shader, keeping the instance buffer
+    pub fn rebuild(
+        &mut self,
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> anyhow::Result<()> {
+        log::debug!("Rebuilding compute instances {:?}", self.source);
+        self.pipeline = Some(Self::create_pipeline(
+            graphics,
+            preprocessor,
+            &self.source,
+            bind_group_layouts,
+            &self.bind_group_layout,
+        )?);
+        Ok(())
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Compute instances bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_pipeline(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        source: &InstanceSource,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        instance_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<wgpu::ComputePipeline> {
+        let shader_source = match source.compute_shader_path() {
+            Some(path) => preprocessor.compile(
+                path,
+                ShaderLanguage::from_path(path),
+                naga::ShaderStage::Compute,
+            )?,
+            None => wgpu::ShaderSource::Wgsl(
+                include_str!("../../../shaders/instances_spectrum.wgsl")
+                    .to_string()
+                    .validated()?
+                    .into(),
+            ),
+        };
+        log::trace!("Creating instance pipeline");
+        let mut pipeline_bind_group_layouts = bind_group_layouts.to_vec();
+        pipeline_bind_group_layouts.push(instance_bind_group_layout);
+        let pipeline_layout =
+            graphics
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Instance Pipeline Layout"),
+                    bind_group_layouts: pipeline_bind_group_layouts.as_slice(),
+                    push_constant_ranges: &[],
+                });
+        let module = graphics
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("Instance Shader Module"),
+                source: shader_source,
+            });
+        Ok(graphics
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Compute Instances Pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: "main",
+            }))
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct InstanceInfo {
+    count: u32,
+    padding: [u32; 3],
+}
+
+pub trait CubensisInstanceComputePass<'a> {
+    ///Runs the instance compute shader of the mesh, if it has one
+    fn generate_mesh_instances(&mut self, mesh: &'a Mesh, bind_groups: &'a [&wgpu::BindGroup]);
+}
+
+impl<'a> CubensisInstanceComputePass<'a> for wgpu::ComputePass<'a> {
+    fn generate_mesh_instances(&mut self, mesh: &'a Mesh, bind_groups: &'a [&wgpu::BindGroup]) {
+        if let Some((compute_instances, pipeline)) = mesh
+            .instances
+            .as_ref()
+            .and_then(|instances| instances.compute_instances.as_ref())
+            .and_then(|compute_instances| {
+                let pipeline = compute_instances.pipeline.as_ref()?;
+                Some((compute_instances, pipeline))
+            })
+        {
+            log::trace!("Generating mesh instances");
+            self.set_pipeline(pipeline);
+            let mut bind_group_index = 0;
+            for bind_group in bind_groups {
+                self.set_bind_group(bind_group_index, bind_group, &[]);
+                bind_group_index += 1;
+            }
+            self.set_bind_group(bind_group_index, &compute_instances.bind_group, &[]);
+            self.dispatch(
+                compute_instances.count.div_ceil(INSTANCE_WORKGROUP_SIZE),
+                1,
+                1,
+            );
+        }
+    }
+}
@@ -1,19 +1,56 @@
 pub mod buffers;
+pub mod compute;
+pub mod file;
+pub mod geometry;
+pub mod instances;
+pub mod particles;
+pub mod primitives;
+pub mod transform;
 
 use crate::device::GraphicsDevice;
-use crate::mesh::buffers::{MeshBuffers, Vertex};
+use crate::mesh::buffers::{Instance, MeshBuffers, Particle, Vertex};
+use crate::mesh::compute::ComputeGeometry;
+use crate::mesh::instances::MeshInstances;
+use crate::mesh::particles::ParticleSimulation;
+use crate::mesh::transform::MeshTransforms;
+use crate::preprocessor::ShaderPreprocessor;
 use crate::resources::CubensisResourceCollection;
-use crate::validation::CubensisValidatedShader;
-use hyphae::scene::geometry::MeshDescriptor;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::scene::geometry::{GeometrySource, MeshDescriptor, ParticleRendering};
 use hyphae::scene::shaders::{RenderShader, RenderShaderBlending};
+use hyphae::scene::transform::Transform;
 use std::rc::Rc;
 use wgpu::{BindGroup, BindGroupLayout, BlendState};
 
+///Drawn in place of render shaders that fail to build
+const ERROR_SHADER: &str = include_str!("../../../shaders/error_shader.wgsl");
+
+///Bind group layouts and attachment formats shared by every mesh drawn in one render pass
+#[derive(Clone, Copy)]
+pub struct PassLayout<'a> {
+    pub history_bind_group_layout: &'a BindGroupLayout,
+    ///Layout of the sampled render graph targets, if the pass has any inputs
+    pub input_bind_group_layout: Option<&'a BindGroupLayout>,
+    pub color_formats: &'a [wgpu::TextureFormat],
+    pub mesh_transforms: &'a Rc<MeshTransforms>,
+    ///Multisample count of the pass attachments
+    pub sample_count: u32,
+    pub preprocessor: &'a Rc<ShaderPreprocessor>,
+}
+
 pub struct Mesh {
     graphics: Rc<GraphicsDevice>,
     buffers: MeshBuffers,
-    compute_pipeline: Option<wgpu::ComputePipeline>,
+    compute_geometry: Option<ComputeGeometry>,
+    particle_simulation: Option<ParticleSimulation>,
+    instances: Option<MeshInstances>,
+    preprocessor: Rc<ShaderPreprocessor>,
+    transforms: Rc<MeshTransforms>,
+    transform_index: usize,
     pub render_pipelines: Vec<wgpu::RenderPipeline>,
+    ///Errors of the shaders that failed to build since they were last taken: render shaders drawn
+    ///with the error pipeline and skipped compute shaders
+    build_errors: Vec<ShaderDiagnostic>,
     pub mesh_descriptor: MeshDescriptor,
 }
 
@@ -22,52 +59,242 @@ impl Mesh {
         graphics: Rc<GraphicsDevice>,
         mesh_descriptor: MeshDescriptor,
         bind_group_layouts: &[&wgpu::BindGroupLayout],
-        history_bind_group_layout: &wgpu::BindGroupLayout,
-    ) -> Self {
+        pass_layout: &PassLayout,
+    ) -> anyhow::Result<Self> {
         log::debug!("Creating mesh");
-        let buffers = MeshBuffers::new(&graphics.device, &mesh_descriptor.geometry_source);
-        let compute_pipeline = None; //todo: revisit compute shader implementation
-        let render_pipelines = Self::create_render_pipelines(
+        let buffers = MeshBuffers::new(
+            &graphics.device,
+            &mesh_descriptor.geometry_source,
+            mesh_descriptor.instance_count,
+        )?;
+        //compute shaders failing to build are skipped until they are fixed
+        let mut compute_build_errors = Vec::new();
+        let compute_geometry = match &mesh_descriptor.geometry_source {
+            GeometrySource::ComputeShader(compute_shader) => Some(ComputeGeometry::new(
+                &graphics,
+                pass_layout.preprocessor,
+                compute_shader.clone(),
+                &buffers,
+                bind_group_layouts,
+                &mut compute_build_errors,
+            )),
+            _ => None,
+        };
+        let particle_simulation = match &mesh_descriptor.geometry_source {
+            GeometrySource::ParticleSystem(particle_system) => Some(ParticleSimulation::new(
+                &graphics,
+                pass_layout.preprocessor,
+                particle_system.clone(),
+                bind_group_layouts,
+                &mut compute_build_errors,
+            )?),
+            _ => None,
+        };
+        //particle systems are instanced by their particle buffer
+        let instances = match &mesh_descriptor.geometry_source {
+            GeometrySource::ParticleSystem(_) => None,
+            _ => Some(MeshInstances::new(
+                &graphics,
+                pass_layout.preprocessor,
+                mesh_descriptor.instance_count,
+                mesh_descriptor.instances.as_ref(),
+                bind_group_layouts,
+                &mut compute_build_errors,
+            )?),
+        };
+        let (render_pipelines, mut build_errors) = Self::create_render_pipelines(
             &graphics,
             &mesh_descriptor,
             bind_group_layouts,
-            history_bind_group_layout,
-        );
-        Self {
+            pass_layout,
+        )?;
+        build_errors.extend(compute_build_errors);
+        let transforms = pass_layout.mesh_transforms.clone();
+        let transform_index = transforms.allocate()?;
+        transforms.write(&graphics, transform_index, &mesh_descriptor.transform);
+        Ok(Self {
             graphics,
             buffers,
-            compute_pipeline,
+            compute_geometry,
+            particle_simulation,
+            instances,
+            preprocessor: pass_layout.preprocessor.clone(),
+            transforms,
+            transform_index,
             render_pipelines,
+            build_errors,
             mesh_descriptor: mesh_descriptor.clone(),
+        })
+    }
+
+    ///True if the geometry is written by a compute shader and has to be drawn indirectly
+    pub fn is_compute_generated(&self) -> bool {
+        self.compute_geometry.is_some()
+    }
+
+    ///Shader path of the geometry compute shader, if any
+    pub fn compute_shader_path(&self) -> Option<&str> {
+        self.compute_geometry
+            .as_ref()
+            .map(|compute_geometry| compute_geometry.compute_shader.path.as_str())
+    }
+
+    ///True if the mesh has compute work to encode before it is drawn
+    pub fn requires_compute_pass(&self) -> bool {
+        self.compute_geometry.is_some()
+            || self.particle_simulation.is_some()
+            || self
+                .instances
+                .as_ref()
+                .is_some_and(|instances| instances.compute_instances.is_some())
+    }
+
+    ///Number of instances drawn, one per particle for particle systems
+    pub fn instance_count(&self) -> u32 {
+        match (&self.particle_simulation, &self.instances) {
+            (Some(particle_simulation), _) => particle_simulation.instance_count(),
+            (None, Some(instances)) => instances.count,
+            (None, None) => 1,
         }
     }
 
-    pub fn resize<'b>(
+    ///Shader path of the instance compute shader, if any
+    pub fn instance_shader_path(&self) -> Option<&str> {
+        self.instances
+            .as_ref()
+            .and_then(|instances| instances.compute_instances.as_ref())
+            .and_then(|compute_instances| compute_instances.source.compute_shader_path())
+    }
+
+    ///Shader paths of the particle emit and update shaders, if any
+    pub fn particle_shader_paths(&self) -> Option<[&str; 2]> {
+        self.particle_simulation
+            .as_ref()
+            .map(|particle_simulation| {
+                [
+                    particle_simulation.particle_system.emit_shader.as_str(),
+                    particle_simulation.particle_system.update_shader.as_str(),
+                ]
+            })
+    }
+
+    ///Library path of the mesh file, if the geometry is loaded from one
+    pub fn geometry_file_path(&self) -> Option<&str> {
+        match &self.mesh_descriptor.geometry_source {
+            GeometrySource::File { path, .. } => Some(path.as_str()),
+            _ => None,
+        }
+    }
+
+    ///Errors of the shaders that failed to build when the mesh was created or resized
+    pub fn take_build_errors(&mut self) -> Vec<ShaderDiagnostic> {
+        std::mem::take(&mut self.build_errors)
+    }
+
+    ///Reloads the geometry from the mesh file after it changed, keeping the render pipelines
+    pub fn reload_geometry(&mut self) -> anyhow::Result<()> {
+        self.buffers = MeshBuffers::new(
+            &self.graphics.device,
+            &self.mesh_descriptor.geometry_source,
+            self.mesh_descriptor.instance_count,
+        )?;
+        Ok(())
+    }
+
+    ///Uploads a new model matrix, taking effect from the next frame
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.mesh_descriptor.transform = transform;
+        self.transforms
+            .write(&self.graphics, self.transform_index, &transform);
+    }
+
+    ///Schedules a simulation step for particle systems
+    pub fn update(&mut self, time_delta: std::time::Duration) {
+        if let Some(particle_simulation) = self.particle_simulation.as_mut() {
+            particle_simulation.update(&self.graphics, time_delta);
+        }
+    }
+
+    ///Recompiles the geometry compute shader after its source changed
+    pub fn rebuild_geometry<ResourceCollection: CubensisResourceCollection>(
+        &mut self,
+        resource_collection: &ResourceCollection,
+    ) -> anyhow::Result<()> {
+        match self.compute_geometry.as_mut() {
+            Some(compute_geometry) => compute_geometry.rebuild(
+                &self.graphics,
+                &self.preprocessor,
+                resource_collection.get_bind_group_layouts().as_slice(),
+            ),
+            None => Err(anyhow::Error::msg("Mesh has no geometry compute shader")),
+        }
+    }
+
+    ///Recompiles the particle emit and update shaders after one of their sources changed
+    pub fn rebuild_particles<ResourceCollection: CubensisResourceCollection>(
+        &mut self,
+        resource_collection: &ResourceCollection,
+    ) -> anyhow::Result<()> {
+        match self.particle_simulation.as_mut() {
+            Some(particle_simulation) => particle_simulation.rebuild(
+                &self.graphics,
+                &self.preprocessor,
+                resource_collection.get_bind_group_layouts().as_slice(),
+            ),
+            None => Err(anyhow::Error::msg("Mesh is not a particle system")),
+        }
+    }
+
+    ///Recompiles the instance compute shader after its source changed
+    pub fn rebuild_instances<ResourceCollection: CubensisResourceCollection>(
+        &mut self,
+        resource_collection: &ResourceCollection,
+    ) -> anyhow::Result<()> {
+        match self
+            .instances
+            .as_mut()
+            .and_then(|instances| instances.compute_instances.as_mut())
+        {
+            Some(compute_instances) => compute_instances.rebuild(
+                &self.graphics,
+                &self.preprocessor,
+                resource_collection.get_bind_group_layouts().as_slice(),
+            ),
+            None => Err(anyhow::Error::msg("Mesh has no instance compute shader")),
+        }
+    }
+
+    pub fn resize(
         &mut self,
-        bind_group_layouts: &'b [&wgpu::BindGroupLayout],
-        history_bind_group_layout: &BindGroupLayout,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        pass_layout: &PassLayout,
     ) {
         log::trace!("Resizing mesh");
-        self.compute_pipeline = None; //todo: revisit compute shader implementation
-        self.render_pipelines = Self::create_render_pipelines(
+        match Self::create_render_pipelines(
             &self.graphics,
             &self.mesh_descriptor,
             bind_group_layouts,
-            history_bind_group_layout,
-        );
+            pass_layout,
+        ) {
+            Ok((render_pipelines, build_errors)) => {
+                self.render_pipelines = render_pipelines;
+                self.build_errors.extend(build_errors);
+            }
+            Err(error) => log::error!("Keeping the previous render pipelines: {}", error),
+        }
     }
 
-    pub fn rebuild<'b, ResourceCollection: CubensisResourceCollection>(
+    pub fn rebuild<ResourceCollection: CubensisResourceCollection>(
         &mut self,
         updated_shader: RenderShader,
         resource_collection: &ResourceCollection,
-        history_bind_group_layout: &BindGroupLayout,
+        pass_layout: &PassLayout,
     ) -> anyhow::Result<()> {
         let index_to_update = self
             .mesh_descriptor
             .render_shaders
-            .binary_search_by_key(&updated_shader.path, |s| s.path.clone())
-            .ok();
+            .iter()
+            .position(|s| s.path == updated_shader.path);
         if index_to_update.is_none() {
             log::debug!("Failed to rebuild shader from path {}", updated_shader.path);
             return Err(anyhow::Error::msg(format!(
@@ -81,8 +308,9 @@ impl Mesh {
         match Self::create_render_pipeline(
             &self.graphics,
             &updated_shader,
+            &self.mesh_descriptor.geometry_source,
             bind_group_layouts.as_slice(),
-            history_bind_group_layout,
+            pass_layout,
         ) {
             Ok(new_pipeline) => {
                 self.render_pipelines[index_to_update] = new_pipeline;
@@ -98,49 +326,168 @@ impl Mesh {
     fn create_render_pipeline(
         graphics: &Rc<GraphicsDevice>,
         render_shader: &RenderShader,
+        geometry_source: &GeometrySource,
         bind_group_layouts: &[&wgpu::BindGroupLayout],
-        history_bind_group_layout: &BindGroupLayout,
+        pass_layout: &PassLayout,
     ) -> anyhow::Result<wgpu::RenderPipeline> {
-        let validated_source = render_shader.get_shader_source().validated()?;
+        let language = render_shader.get_language();
+        let vertex_source = pass_layout.preprocessor.compile(
+            &render_shader.path,
+            language,
+            naga::ShaderStage::Vertex,
+        )?;
+        //GLSL modules hold a single stage
+        let fragment_source = if language.is_glsl() {
+            Some(pass_layout.preprocessor.compile(
+                &render_shader.path,
+                language,
+                naga::ShaderStage::Fragment,
+            )?)
+        } else {
+            None
+        };
+        let blend = render_shader.get_blend_mode().into_blend_state_option();
+        let color_targets: Vec<wgpu::ColorTargetState> = pass_layout
+            .color_formats
+            .iter()
+            .map(|format| wgpu::ColorTargetState {
+                format: *format,
+                blend: Self::supported_blend(*format, blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+            .collect();
+        graphics.capture_errors(|| {
+            Self::create_pipeline_from_source(
+                graphics,
+                vertex_source,
+                fragment_source,
+                color_targets.as_slice(),
+                geometry_source,
+                bind_group_layouts,
+                pass_layout,
+            )
+        })
+    }
 
+    ///32 bit float formats can't be blended, shaders drawing to them always replace the target
+    fn supported_blend(
+        format: wgpu::TextureFormat,
+        blend: Option<BlendState>,
+    ) -> Option<BlendState> {
+        if format.describe().guaranteed_format_features.filterable {
+            blend
+        } else {
+            None
+        }
+    }
+
+    ///Pipeline drawing a magenta checkerboard in place of a render shader that failed to build
+    fn create_error_pipeline(
+        graphics: &Rc<GraphicsDevice>,
+        geometry_source: &GeometrySource,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        pass_layout: &PassLayout,
+    ) -> anyhow::Result<wgpu::RenderPipeline> {
+        log::trace!("Creating error render pipeline");
+        //the error shader only writes the first target
+        let color_targets: Vec<wgpu::ColorTargetState> = pass_layout
+            .color_formats
+            .iter()
+            .enumerate()
+            .map(|(index, format)| wgpu::ColorTargetState {
+                format: *format,
+                blend: None,
+                write_mask: if index == 0 {
+                    wgpu::ColorWrites::ALL
+                } else {
+                    wgpu::ColorWrites::empty()
+                },
+            })
+            .collect();
+        graphics.capture_errors(|| {
+            Self::create_pipeline_from_source(
+                graphics,
+                wgpu::ShaderSource::Wgsl(ERROR_SHADER.into()),
+                None,
+                color_targets.as_slice(),
+                geometry_source,
+                bind_group_layouts,
+                pass_layout,
+            )
+        })
+    }
+
+    ///The vertex module also runs the fragment stage unless `fragment_source` is given
+    fn create_pipeline_from_source(
+        graphics: &Rc<GraphicsDevice>,
+        vertex_source: wgpu::ShaderSource,
+        fragment_source: Option<wgpu::ShaderSource>,
+        color_targets: &[wgpu::ColorTargetState],
+        geometry_source: &GeometrySource,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        pass_layout: &PassLayout,
+    ) -> wgpu::RenderPipeline {
         log::trace!("Creating render pipeline");
+        let mut pipeline_bind_group_layouts = bind_group_layouts.to_vec();
+        pipeline_bind_group_layouts.push(pass_layout.history_bind_group_layout);
+        pipeline_bind_group_layouts.push(
+            pass_layout
+                .input_bind_group_layout
+                .unwrap_or(&pass_layout.mesh_transforms.empty_bind_group_layout),
+        );
+        pipeline_bind_group_layouts.push(&pass_layout.mesh_transforms.bind_group_layout);
         let render_pipeline_layout =
             graphics
                 .device
                 .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: [bind_group_layouts, &[history_bind_group_layout]]
-                        .concat()
-                        .as_slice(),
+                    bind_group_layouts: pipeline_bind_group_layouts.as_slice(),
                     push_constant_ranges: &[],
                 });
-        let module = graphics
-            .device
-            .create_shader_module(&wgpu::ShaderModuleDescriptor {
-                label: Some("Render Shader Module"),
-                source: wgpu::ShaderSource::Wgsl(validated_source.into()),
-            });
-        Ok(graphics
+        let (vertex_buffers, topology) = match geometry_source {
+            GeometrySource::ParticleSystem(particle_system) => (
+                vec![Vertex::desc(), Particle::desc()],
+                match particle_system.rendering {
+                    ParticleRendering::Billboards => wgpu::PrimitiveTopology::TriangleList,
+                    ParticleRendering::Points => wgpu::PrimitiveTopology::PointList,
+                },
+            ),
+            GeometrySource::Primitive(primitive) if primitive.is_line() => (
+                vec![Vertex::desc(), Instance::desc()],
+                wgpu::PrimitiveTopology::LineList,
+            ),
+            _ => (
+                vec![Vertex::desc(), Instance::desc()],
+                wgpu::PrimitiveTopology::TriangleList,
+            ),
+        };
+        let create_module = |source| {
+            graphics
+                .device
+                .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                    label: Some("Render Shader Module"),
+                    source,
+                })
+        };
+        let vertex_module = create_module(vertex_source);
+        let fragment_module = fragment_source.map(create_module);
+        graphics
             .device
             .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                 label: Some("Render Pipeline"),
                 layout: Some(&render_pipeline_layout),
                 vertex: wgpu::VertexState {
-                    module: &module,
+                    module: &vertex_module,
                     entry_point: "main",
-                    buffers: &[Vertex::desc()],
+                    buffers: vertex_buffers.as_slice(),
                 },
                 fragment: Some(wgpu::FragmentState {
-                    module: &module,
+                    module: fragment_module.as_ref().unwrap_or(&vertex_module),
                     entry_point: "main",
-                    targets: &[wgpu::ColorTargetState {
-                        format: graphics.get_format(),
-                        blend: render_shader.get_blend_mode().into_blend_state_option(),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    }],
+                    targets: color_targets,
                 }),
                 primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    topology,
                     strip_index_format: None,
                     front_face: wgpu::FrontFace::Cw,
                     cull_mode: Some(wgpu::Face::Back),
@@ -156,100 +503,160 @@ impl Mesh {
                     bias: wgpu::DepthBiasState::default(),
                 }),
                 multisample: wgpu::MultisampleState {
-                    count: 1,
+                    count: pass_layout.sample_count,
                     mask: !0,
                     alpha_to_coverage_enabled: false,
                 },
-            }))
+            })
     }
 
+    ///Shaders failing to build are drawn with the error pipeline, their errors are returned alongside.
+    ///Fails only if the error pipeline can't be built either.
     fn create_render_pipelines(
         graphics: &Rc<GraphicsDevice>,
         mesh_descriptor: &MeshDescriptor,
         bind_group_layouts: &[&wgpu::BindGroupLayout],
-        history_bind_group_layout: &BindGroupLayout,
-    ) -> Vec<wgpu::RenderPipeline> {
+        pass_layout: &PassLayout,
+    ) -> anyhow::Result<(Vec<wgpu::RenderPipeline>, Vec<ShaderDiagnostic>)> {
         log::trace!("Creating render pipelines for mesh");
-        mesh_descriptor
+        let mut build_errors = Vec::new();
+        let render_pipelines = mesh_descriptor
             .render_shaders
             .iter()
             .map(|s| {
                 Self::create_render_pipeline(
                     graphics,
                     s,
+                    &mesh_descriptor.geometry_source,
                     bind_group_layouts,
-                    history_bind_group_layout,
+                    pass_layout,
                 )
-                .unwrap() //todo: address this
+                .or_else(|error| {
+                    let diagnostic = ShaderDiagnostic::from_error(&s.path, &error);
+                    log::warn!(
+                        "Drawing failed shader with the error shader: {}",
+                        diagnostic
+                    );
+                    build_errors.push(diagnostic);
+                    Self::create_error_pipeline(
+                        graphics,
+                        &mesh_descriptor.geometry_source,
+                        bind_group_layouts,
+                        pass_layout,
+                    )
+                })
             })
-            .collect()
+            .collect::<anyhow::Result<_>>()?;
+        Ok((render_pipelines, build_errors))
     }
 }
 
 pub trait CubensisMeshRenderPass<'a> {
-    fn draw_mesh_indexed(
+    ///Draws the mesh with only its render shader at `shader_index`
+    fn draw_mesh_shader_indexed(
+        &mut self,
+        mesh: &'a Mesh,
+        shader_index: usize,
+        bind_groups: &'a [&wgpu::BindGroup],
+        history_bind_group: &'a BindGroup,
+        input_bind_group: Option<&'a BindGroup>,
+    );
+
+    ///Draws the mesh with only its render shader at `shader_index`, using the indirect arguments
+    fn draw_mesh_shader_indirect(
         &mut self,
         mesh: &'a Mesh,
+        shader_index: usize,
         bind_groups: &'a [&wgpu::BindGroup],
         history_bind_group: &'a BindGroup,
+        input_bind_group: Option<&'a BindGroup>,
     );
 
-    fn draw_mesh_indirect(
+    fn bind_mesh(
         &mut self,
         mesh: &'a Mesh,
+        shader_index: usize,
         bind_groups: &'a [&wgpu::BindGroup],
         history_bind_group: &'a BindGroup,
+        input_bind_group: Option<&'a BindGroup>,
     );
 }
 
 impl<'a> CubensisMeshRenderPass<'a> for wgpu::RenderPass<'a> {
-    fn draw_mesh_indexed(
+    fn draw_mesh_shader_indexed(
         &mut self,
         mesh: &'a Mesh,
+        shader_index: usize,
         bind_groups: &'a [&wgpu::BindGroup],
         history_bind_group: &'a BindGroup,
+        input_bind_group: Option<&'a BindGroup>,
     ) {
-        log::trace!("Drawing indexed mesh");
-
-        for render_pipeline in &mesh.render_pipelines {
-            self.set_pipeline(render_pipeline);
-            let mut bind_group_index = 0;
-            for bind_group in bind_groups {
-                self.set_bind_group(bind_group_index as u32, *bind_group, &[]);
-                bind_group_index += 1;
-            }
-            self.set_bind_group(bind_group_index, history_bind_group, &[]);
-            self.set_vertex_buffer(0, mesh.buffers.vertex_buffer.slice(..));
-            self.set_index_buffer(
-                mesh.buffers.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint16,
-            );
-            self.draw_indexed(0..mesh.buffers.index_count, 0, 0..1);
-        }
+        log::trace!("Drawing indexed mesh with render shader {}", shader_index);
+        self.bind_mesh(
+            mesh,
+            shader_index,
+            bind_groups,
+            history_bind_group,
+            input_bind_group,
+        );
+        self.draw_indexed(0..mesh.buffers.index_count, 0, 0..mesh.instance_count());
     }
 
-    fn draw_mesh_indirect(
+    fn draw_mesh_shader_indirect(
         &mut self,
         mesh: &'a Mesh,
+        shader_index: usize,
         bind_groups: &'a [&wgpu::BindGroup],
         history_bind_group: &'a BindGroup,
+        input_bind_group: Option<&'a BindGroup>,
     ) {
-        log::trace!("Drawing indirect mesh");
-        for render_pipeline in &mesh.render_pipelines {
-            self.set_pipeline(render_pipeline);
-            let mut bind_group_index = 0;
-            for bind_group in bind_groups {
-                self.set_bind_group(bind_group_index as u32, *bind_group, &[]);
-                bind_group_index += 1;
-            }
-            self.set_bind_group(bind_group_index, history_bind_group, &[]);
-            self.set_vertex_buffer(0, mesh.buffers.vertex_buffer.slice(..));
-            self.set_index_buffer(
-                mesh.buffers.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint16,
-            );
-            self.draw_indexed_indirect(&mesh.buffers.indirect_buffer, 0);
+        log::trace!("Drawing indirect mesh with render shader {}", shader_index);
+        self.bind_mesh(
+            mesh,
+            shader_index,
+            bind_groups,
+            history_bind_group,
+            input_bind_group,
+        );
+        self.draw_indexed_indirect(&mesh.buffers.indirect_buffer, 0);
+    }
+
+    fn bind_mesh(
+        &mut self,
+        mesh: &'a Mesh,
+        shader_index: usize,
+        bind_groups: &'a [&wgpu::BindGroup],
+        history_bind_group: &'a BindGroup,
+        input_bind_group: Option<&'a BindGroup>,
+    ) {
+        self.set_pipeline(&mesh.render_pipelines[shader_index]);
+        let mut bind_group_index = 0;
+        for bind_group in bind_groups {
+            self.set_bind_group(bind_group_index, bind_group, &[]);
+            bind_group_index += 1;
+        }
+        self.set_bind_group(bind_group_index, history_bind_group, &[]);
+        self.set_bind_group(
+            bind_group_index + 1,
+            input_bind_group.unwrap_or(&mesh.transforms.empty_bind_group),
+            &[],
+        );
+        self.set_bind_group(
+            bind_group_index + 2,
+            &mesh.transforms.bind_group,
+            &[mesh.transforms.offset(mesh.transform_index)],
+        );
+        self.set_vertex_buffer(0, mesh.buffers.vertex_buffer.slice(..));
+        if let Some(particle_simulation) = &mesh.particle_simulation {
+            self.set_vertex_buffer(1, particle_simulation.particle_buffer.slice(..));
+        }
+        if let Some(instances) = &mesh.instances {
+            self.set_vertex_buffer(1, instances.instance_buffer.slice(..));
         }
+        self.set_index_buffer(
+            mesh.buffers.index_buffer.slice(..),
+            mesh.buffers.index_format,
+        );
     }
 }
 
@@ -0,0 +1,296 @@
+use crate::device::GraphicsDevice;
+use crate::mesh::buffers::Particle;
+use crate::mesh::Mesh;
+use crate::preprocessor::ShaderPreprocessor;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::scene::geometry::ParticleSystem;
+use hyphae::scene::shaders::ShaderLanguage;
+use std::cell::Cell;
+use wgpu::util::DeviceExt;
+
+///Workgroup size the emit and update shaders have to declare
+pub const PARTICLE_WORKGROUP_SIZE: u32 = 64;
+
+///Particle state buffer with the emit and update pipelines simulating it.
+///Bound after the resource groups at bindings 0 (particles) and 1 (`ParticleSystemInfo` uniform),
+///the state buffer doubles as the instance buffer of the mesh.
+pub(crate) struct ParticleSimulation {
+    pub(crate) particle_system: ParticleSystem,
+    pub(crate) particle_buffer: wgpu::Buffer,
+    info_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    ///Emit and update pipelines, `None` while either shader fails to build, which pauses the simulation
+    pipelines: Option<(wgpu::ComputePipeline, wgpu::ComputePipeline)>,
+    info: ParticleSystemInfo,
+    spawn_remainder: f32,
+    //set by `update`, so frames drawn with frozen resources don't advance the simulation
+    step_pending: Cell<bool>,
+}
+
+impl ParticleSimulation {
+    ///Emit or update shaders failing to build have their error pushed to `build_errors`
+    pub fn new(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        particle_system: ParticleSystem,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        build_errors: &mut Vec<ShaderDiagnostic>,
+    ) -> anyhow::Result<Self> {
+        log::debug!("Creating particle system {}", particle_system.name);
+        let particle_buffer = Self::create_particle_buffer(graphics, &particle_system)?;
+        let info = ParticleSystemInfo {
+            capacity: particle_system.capacity,
+            ..Default::default()
+        };
+        let info_buffer = graphics
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Particle System Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[info]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let bind_group_layout = Self::create_bind_group_layout(&graphics.device);
+        let bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Particle system bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: particle_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: info_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+        let pipelines = Self::create_pipelines(
+            graphics,
+            preprocessor,
+            &particle_system,
+            bind_group_layouts,
+            &bind_group_layout,
+        )
+        .map_err(|error| {
+            let diagnostic = ShaderDiagnostic::from_error(&particle_system.emit_shader, &error);
+            log::warn!(
+                "Pausing particle system with failed shaders: {}",
+                diagnostic
+            );
+            build_errors.push(diagnostic);
+        })
+        .ok();
+        Ok(Self {
+            particle_system,
+            particle_buffer,
+            info_buffer,
+            bind_group_layout,
+            bind_group,
+            pipelines,
+            info,
+            spawn_remainder: 0.0,
+            step_pending: Cell::new(false),
+        })
+    }
+
+    ///Recompiles the emit and update shaders, keeping the particle state
+    pub fn rebuild(
+        &mut self,
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> anyhow::Result<()> {
+        log::debug!("Rebuilding particle system {}", self.particle_system.name);
+        self.pipelines = Some(Self::create_pipelines(
+            graphics,
+            preprocessor,
+            &self.particle_system,
+            bind_group_layouts,
+            &self.bind_group_layout,
+        )?);
+        Ok(())
+    }
+
+    ///Schedules one simulation step of `time_delta`, spawning particles at the configured rate
+    pub fn update(&mut self, graphics: &GraphicsDevice, time_delta: std::time::Duration) {
+        log::trace!("Updating particle system {}", self.particle_system.name);
+        let capacity = self.particle_system.capacity;
+        let spawn =
+            self.spawn_remainder + self.particle_system.spawn_rate * time_delta.as_secs_f32();
+        let spawn_count = spawn.max(0.0).floor();
+        self.spawn_remainder = spawn - spawn_count;
+        self.info = ParticleSystemInfo {
+            capacity,
+            spawn_count: (spawn_count as u32).min(capacity),
+            spawn_offset: (self.info.spawn_offset + self.info.spawn_count) % capacity,
+            time_delta: time_delta.as_secs_f32(),
+            step_index: self.info.step_index.wrapping_add(1),
+            ..Default::default()
+        };
+        graphics
+            .queue
+            .write_buffer(&self.info_buffer, 0, bytemuck::cast_slice(&[self.info]));
+        self.step_pending.set(true);
+    }
+
+    pub fn instance_count(&self) -> u32 {
+        self.particle_system.capacity
+    }
+
+    fn create_particle_buffer(
+        graphics: &GraphicsDevice,
+        particle_system: &ParticleSystem,
+    ) -> anyhow::Result<wgpu::Buffer> {
+        let size = particle_system.capacity as u64 * std::mem::size_of::<Particle>() as u64;
+        let max_size = graphics.device.limits().max_storage_buffer_binding_size as u64;
+        if size == 0 || size > max_size {
+            return Err(anyhow::Error::msg(format!(
+                "Capacity of particle system {} must be at least 1 and fit the {} byte storage buffer limit",
+                particle_system.name, max_size
+            )));
+        }
+        //zeroed, so every particle starts out dead
+        Ok(graphics.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Storage Buffer"),
+            size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        }))
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle system bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_pipelines(
+        graphics: &GraphicsDevice,
+        preprocessor: &ShaderPreprocessor,
+        particle_system: &ParticleSystem,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        particle_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<(wgpu::ComputePipeline, wgpu::ComputePipeline)> {
+        let compile = |path: &str| {
+            preprocessor.compile(
+                path,
+                ShaderLanguage::from_path(path),
+                naga::ShaderStage::Compute,
+            )
+        };
+        let emit_source = compile(&particle_system.emit_shader)?;
+        let update_source = compile(&particle_system.update_shader)?;
+        log::trace!("Creating particle pipelines");
+        let mut pipeline_bind_group_layouts = bind_group_layouts.to_vec();
+        pipeline_bind_group_layouts.push(particle_bind_group_layout);
+        let pipeline_layout =
+            graphics
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Particle Pipeline Layout"),
+                    bind_group_layouts: pipeline_bind_group_layouts.as_slice(),
+                    push_constant_ranges: &[],
+                });
+        let create_pipeline = |label, source| {
+            let module = graphics
+                .device
+                .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                    label: Some(label),
+                    source,
+                });
+            graphics
+                .device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: Some(label),
+                    layout: Some(&pipeline_layout),
+                    module: &module,
+                    entry_point: "main",
+                })
+        };
+        Ok((
+            create_pipeline("Particle Emit Pipeline", emit_source),
+            create_pipeline("Particle Update Pipeline", update_source),
+        ))
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct ParticleSystemInfo {
+    capacity: u32,
+    spawn_count: u32,
+    spawn_offset: u32,
+    time_delta: f32,
+    step_index: u32,
+    padding: [u32; 3],
+}
+
+pub trait CubensisParticleComputePass<'a> {
+    ///Emits and then updates the particles of the mesh, if it is a particle system with a pending step
+    fn simulate_particles(&mut self, mesh: &'a Mesh, bind_groups: &'a [&wgpu::BindGroup]);
+}
+
+impl<'a> CubensisParticleComputePass<'a> for wgpu::ComputePass<'a> {
+    fn simulate_particles(&mut self, mesh: &'a Mesh, bind_groups: &'a [&wgpu::BindGroup]) {
+        if let Some(simulation) = &mesh.particle_simulation {
+            if !simulation.step_pending.replace(false) {
+                return;
+            }
+            let (emit_pipeline, update_pipeline) = match &simulation.pipelines {
+                Some(pipelines) => pipelines,
+                None => return,
+            };
+            log::trace!("Simulating particles");
+            let mut bind_group_index = 0;
+            for bind_group in bind_groups {
+                self.set_bind_group(bind_group_index, bind_group, &[]);
+                bind_group_index += 1;
+            }
+            self.set_bind_group(bind_group_index, &simulation.bind_group, &[]);
+            if simulation.info.spawn_count > 0 {
+                self.set_pipeline(emit_pipeline);
+                self.dispatch(
+                    simulation
+                        .info
+                        .spawn_count
+                        .div_ceil(PARTICLE_WORKGROUP_SIZE),
+                    1,
+                    1,
+                );
+            }
+            self.set_pipeline(update_pipeline);
+            self.dispatch(
+                simulation
+                    .instance_count()
+                    .div_ceil(PARTICLE_WORKGROUP_SIZE),
+                1,
+                1,
+            );
+        }
+    }
+}
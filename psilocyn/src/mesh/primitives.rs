@@ -0,0 +1,320 @@
+use crate::mesh::buffers::Vertex;
+use crate::mesh::geometry::{normalize, Geometry};
+use hyphae::scene::primitives::PrimitiveType;
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+///Deepest icosphere subdivision, already over five million triangles
+const MAX_SUBDIVISIONS: u32 = 9;
+
+impl Geometry {
+    ///Generates a parametric primitive with normals, and tangents for triangle primitives
+    pub fn from_primitive(primitive: &PrimitiveType) -> Self {
+        log::debug!("Generating primitive {:?}", primitive);
+        let mut geometry = match primitive {
+            PrimitiveType::Quad => Self::plane_grid(2.0, 2.0, 1, 1),
+            PrimitiveType::Cube { size } => Self::cube(*size),
+            PrimitiveType::UvSphere {
+                radius,
+                segments,
+                rings,
+            } => Self::uv_sphere(
+                *radius,
+                clamp_resolution(*segments, 3, "segments"),
+                clamp_resolution(*rings, 2, "rings"),
+            ),
+            PrimitiveType::Icosphere {
+                radius,
+                subdivisions,
+            } => Self::icosphere(*radius, clamp_subdivisions(*subdivisions)),
+            PrimitiveType::Torus {
+                major_radius,
+                minor_radius,
+                major_segments,
+                minor_segments,
+            } => Self::torus(
+                *major_radius,
+                *minor_radius,
+                clamp_resolution(*major_segments, 3, "major_segments"),
+                clamp_resolution(*minor_segments, 3, "minor_segments"),
+            ),
+            PrimitiveType::Cylinder {
+                radius,
+                height,
+                segments,
+                capped,
+            } => Self::cylinder(
+                *radius,
+                *height,
+                clamp_resolution(*segments, 3, "segments"),
+                *capped,
+            ),
+            PrimitiveType::PlaneGrid {
+                width,
+                height,
+                columns,
+                rows,
+            } => Self::plane_grid(
+                *width,
+                *height,
+                clamp_resolution(*columns, 1, "columns"),
+                clamp_resolution(*rows, 1, "rows"),
+            ),
+            PrimitiveType::LineStrip { points } => Self::line_strip(points),
+            PrimitiveType::Circle { radius, segments } => {
+                Self::circle(*radius, clamp_resolution(*segments, 3, "segments"))
+            }
+        };
+        if !primitive.is_line() {
+            geometry.compute_tangents(0);
+            geometry.flip_winding();
+        }
+        geometry
+    }
+
+    fn cube(size: f32) -> Self {
+        let half = size / 2.0;
+        let mut geometry = Self::empty();
+        //normal, then two axes spanning the face with u x v = normal
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        for (normal, u, v) in faces {
+            let first = geometry.vertices.len() as u32;
+            for (corner_u, corner_v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                let position = [0, 1, 2].map(|axis| {
+                    half * (normal[axis]
+                        + (corner_u * 2.0 - 1.0) * u[axis]
+                        + (corner_v * 2.0 - 1.0) * v[axis])
+                });
+                geometry
+                    .vertices
+                    .push(Vertex::new(position, [corner_u, corner_v]).with_normal(normal));
+            }
+            geometry.indices.extend_from_slice(&[
+                first,
+                first + 1,
+                first + 2,
+                first,
+                first + 2,
+                first + 3,
+            ]);
+        }
+        geometry
+    }
+
+    fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Self {
+        Self::grid(segments, rings, |u, v| {
+            let (theta, phi) = (v * PI, u * TAU);
+            let normal = [
+                theta.sin() * phi.cos(),
+                theta.cos(),
+                theta.sin() * phi.sin(),
+            ];
+            Vertex::new(normal.map(|axis| axis * radius), [u, 1.0 - v]).with_normal(normal)
+        })
+    }
+
+    fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+        let mut positions: Vec<[f32; 3]> = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .iter()
+        .map(|position| normalize(*position))
+        .collect();
+        #[rustfmt::skip]
+        let mut indices: Vec<u32> = vec![
+            0, 11, 5, 0, 5, 1, 0, 1, 7, 0, 7, 10, 0, 10, 11,
+            1, 5, 9, 5, 11, 4, 11, 10, 2, 10, 7, 6, 7, 1, 8,
+            3, 9, 4, 3, 4, 2, 3, 2, 6, 3, 6, 8, 3, 8, 9,
+            4, 9, 5, 2, 4, 11, 6, 2, 10, 8, 6, 7, 9, 8, 1,
+        ];
+        for _ in 0..subdivisions {
+            let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+            let mut midpoint = |a: u32, b: u32, positions: &mut Vec<[f32; 3]>| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let [pa, pb] = [positions[a as usize], positions[b as usize]];
+                    positions.push(normalize([0, 1, 2].map(|axis| pa[axis] + pb[axis])));
+                    positions.len() as u32 - 1
+                })
+            };
+            let mut subdivided = Vec::with_capacity(indices.len() * 4);
+            for triangle in indices.chunks_exact(3) {
+                let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+                let ab = midpoint(a, b, &mut positions);
+                let bc = midpoint(b, c, &mut positions);
+                let ca = midpoint(c, a, &mut positions);
+                subdivided.extend_from_slice(&[a, ab, ca, b, bc, ab, c, ca, bc, ab, bc, ca]);
+            }
+            indices = subdivided;
+        }
+        let vertices = positions
+            .iter()
+            .map(|[x, y, z]| {
+                let uv = [0.5 + z.atan2(*x) / TAU, 0.5 + y.asin() / PI];
+                Vertex::new([x * radius, y * radius, z * radius], uv).with_normal([*x, *y, *z])
+            })
+            .collect();
+        Self { vertices, indices }
+    }
+
+    fn torus(
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: u32,
+        minor_segments: u32,
+    ) -> Self {
+        Self::grid(minor_segments, major_segments, |u, v| {
+            let (theta, phi) = (u * TAU, v * TAU);
+            let distance = major_radius + minor_radius * theta.cos();
+            let position = [
+                distance * phi.cos(),
+                minor_radius * theta.sin(),
+                distance * phi.sin(),
+            ];
+            let normal = [
+                theta.cos() * phi.cos(),
+                theta.sin(),
+                theta.cos() * phi.sin(),
+            ];
+            Vertex::new(position, [u, v]).with_normal(normal)
+        })
+    }
+
+    fn cylinder(radius: f32, height: f32, segments: u32, capped: bool) -> Self {
+        let half = height / 2.0;
+        let mut geometry = Self::grid(segments, 1, |u, v| {
+            let phi = u * TAU;
+            let position = [radius * phi.cos(), half - v * height, radius * phi.sin()];
+            Vertex::new(position, [u, 1.0 - v]).with_normal([phi.cos(), 0.0, phi.sin()])
+        });
+        if capped {
+            for y in [half, -half] {
+                let center = geometry.vertices.len() as u32;
+                let normal = [0.0, y.signum(), 0.0];
+                geometry
+                    .vertices
+                    .push(Vertex::new([0.0, y, 0.0], [0.5, 0.5]).with_normal(normal));
+                for segment in 0..segments {
+                    let phi = segment as f32 / segments as f32 * TAU;
+                    geometry.vertices.push(
+                        Vertex::new(
+                            [radius * phi.cos(), y, radius * phi.sin()],
+                            [0.5 + 0.5 * phi.cos(), 0.5 + 0.5 * phi.sin()],
+                        )
+                        .with_normal(normal),
+                    );
+                }
+                for segment in 0..segments {
+                    let current = center + 1 + segment;
+                    let next = center + 1 + (segment + 1) % segments;
+                    if y > 0.0 {
+                        geometry.indices.extend_from_slice(&[center, next, current]);
+                    } else {
+                        geometry.indices.extend_from_slice(&[center, current, next]);
+                    }
+                }
+            }
+        }
+        geometry
+    }
+
+    fn plane_grid(width: f32, height: f32, columns: u32, rows: u32) -> Self {
+        Self::grid(columns, rows, |u, v| {
+            let position = [(u - 0.5) * width, (v - 0.5) * height, 0.0];
+            Vertex::new(position, [u, v]).with_normal([0.0, 0.0, 1.0])
+        })
+    }
+
+    fn line_strip(points: &[[f32; 3]]) -> Self {
+        if points.len() < 2 {
+            log::warn!("Line strip needs at least two points");
+        }
+        let last = points.len().saturating_sub(1).max(1) as f32;
+        let vertices = points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| Vertex::new(*point, [index as f32 / last, 0.0]))
+            .collect();
+        let indices = (1..points.len() as u32)
+            .flat_map(|index| [index - 1, index])
+            .collect();
+        Self { vertices, indices }
+    }
+
+    fn circle(radius: f32, segments: u32) -> Self {
+        let vertices = (0..segments)
+            .map(|segment| {
+                let u = segment as f32 / segments as f32;
+                let phi = u * TAU;
+                Vertex::new([radius * phi.cos(), radius * phi.sin(), 0.0], [u, 0.0])
+            })
+            .collect();
+        let indices = (0..segments)
+            .flat_map(|segment| [segment, (segment + 1) % segments])
+            .collect();
+        Self { vertices, indices }
+    }
+
+    ///A `(columns + 1) x (rows + 1)` vertex grid from `vertex(u, v)` with `u, v` in `0..=1`.
+    ///Triangles face the side `d/du x d/dv` points to.
+    fn grid<F: Fn(f32, f32) -> Vertex>(columns: u32, rows: u32, vertex: F) -> Self {
+        let mut vertices = Vec::with_capacity(((columns + 1) * (rows + 1)) as usize);
+        for row in 0..=rows {
+            for column in 0..=columns {
+                vertices.push(vertex(
+                    column as f32 / columns as f32,
+                    row as f32 / rows as f32,
+                ));
+            }
+        }
+        let mut indices = Vec::with_capacity((columns * rows * 6) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = row * (columns + 1) + column;
+                let b = a + columns + 1;
+                indices.extend_from_slice(&[a, a + 1, b, a + 1, b + 1, b]);
+            }
+        }
+        Self { vertices, indices }
+    }
+}
+
+fn clamp_resolution(value: u32, minimum: u32, name: &str) -> u32 {
+    if value < minimum {
+        log::warn!("Primitive {} {} is too low, using {}", name, value, minimum);
+        minimum
+    } else {
+        value
+    }
+}
+
+fn clamp_subdivisions(subdivisions: u32) -> u32 {
+    if subdivisions > MAX_SUBDIVISIONS {
+        log::warn!(
+            "Icosphere subdivision {} is too deep, using {}",
+            subdivisions,
+            MAX_SUBDIVISIONS
+        );
+        MAX_SUBDIVISIONS
+    } else {
+        subdivisions
+    }
+}
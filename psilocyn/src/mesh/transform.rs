@@ -0,0 +1,161 @@
+use crate::device::GraphicsDevice;
+use hyphae::scene::transform::Transform;
+use std::cell::Cell;
+
+///Model and normal matrices of every mesh in a render graph, one uniform buffer slot per mesh.
+///Render shaders read them at group 3, binding 0; passes without inputs bind an empty group 2.
+pub struct MeshTransforms {
+    buffer: wgpu::Buffer,
+    stride: u64,
+    capacity: usize,
+    allocated: Cell<usize>,
+    pub(crate) bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) bind_group: wgpu::BindGroup,
+    pub(crate) empty_bind_group_layout: wgpu::BindGroupLayout,
+    pub(crate) empty_bind_group: wgpu::BindGroup,
+}
+
+impl MeshTransforms {
+    pub fn new(graphics: &GraphicsDevice, mesh_count: usize) -> Self {
+        log::debug!("Creating transforms for {} meshes", mesh_count);
+        let alignment = graphics.device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride =
+            (std::mem::size_of::<TransformUniform>() as u64).div_ceil(alignment) * alignment;
+        let buffer = graphics.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Mesh Transform Uniform Buffer"),
+            size: stride * mesh_count.max(1) as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout =
+            graphics
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Mesh transform bind group layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<
+                                TransformUniform,
+                            >()
+                                as u64),
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mesh transform bind group"),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<TransformUniform>() as u64),
+                    }),
+                }],
+            });
+        let empty_bind_group_layout =
+            graphics
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Empty bind group layout"),
+                    entries: &[],
+                });
+        let empty_bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Empty bind group"),
+                layout: &empty_bind_group_layout,
+                entries: &[],
+            });
+        Self {
+            buffer,
+            stride,
+            capacity: mesh_count,
+            allocated: Cell::new(0),
+            bind_group_layout,
+            bind_group,
+            empty_bind_group_layout,
+            empty_bind_group,
+        }
+    }
+
+    ///Index of the next free slot, taken by a mesh for its lifetime
+    pub fn allocate(&self) -> anyhow::Result<usize> {
+        let index = self.allocated.get();
+        if index >= self.capacity {
+            return Err(anyhow::Error::msg(format!(
+                "All {} mesh transform slots are taken",
+                self.capacity
+            )));
+        }
+        self.allocated.set(index + 1);
+        Ok(index)
+    }
+
+    ///Dynamic offset of the slot at `index`
+    pub fn offset(&self, index: usize) -> u32 {
+        (self.stride * index as u64) as u32
+    }
+
+    pub fn write(&self, graphics: &GraphicsDevice, index: usize, transform: &Transform) {
+        log::trace!("Writing mesh transform {:?}", transform);
+        graphics.queue.write_buffer(
+            &self.buffer,
+            self.offset(index) as u64,
+            bytemuck::cast_slice(&[TransformUniform::from(transform)]),
+        );
+    }
+}
+
+///The normal matrix is the inverse transpose of the model matrix, padded to a `mat4x4`
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TransformUniform {
+    model: [[f32; 4]; 4],
+    normal: [[f32; 4]; 4],
+}
+
+impl From<&Transform> for TransformUniform {
+    fn from(transform: &Transform) -> Self {
+        let [x, y, z] = transform.rotation.map(f32::to_radians);
+        let (sin_x, cos_x) = x.sin_cos();
+        let (sin_y, cos_y) = y.sin_cos();
+        let (sin_z, cos_z) = z.sin_cos();
+        //columns of Rz * Ry * Rx
+        let rotation = [
+            [cos_y * cos_z, cos_y * sin_z, -sin_y],
+            [
+                sin_x * sin_y * cos_z - cos_x * sin_z,
+                sin_x * sin_y * sin_z + cos_x * cos_z,
+                sin_x * cos_y,
+            ],
+            [
+                cos_x * sin_y * cos_z + sin_x * sin_z,
+                cos_x * sin_y * sin_z - sin_x * cos_z,
+                cos_x * cos_y,
+            ],
+        ];
+        let mut model = [[0.0; 4]; 4];
+        let mut normal = [[0.0; 4]; 4];
+        for axis in 0..3 {
+            let scale = transform.scale[axis];
+            //a zero scale flattens the mesh, its normals are left undefined
+            let inverse_scale = if scale != 0.0 { 1.0 / scale } else { 0.0 };
+            for row in 0..3 {
+                model[axis][row] = rotation[axis][row] * scale;
+                normal[axis][row] = rotation[axis][row] * inverse_scale;
+            }
+        }
+        let [tx, ty, tz] = transform.translation;
+        model[3] = [tx, ty, tz, 1.0];
+        normal[3][3] = 1.0;
+        Self { model, normal }
+    }
+}
@@ -0,0 +1,440 @@
+use crate::device::GraphicsDevice;
+use crate::mesh::buffers::{MeshBuffers, Vertex};
+use crate::validation::CubensisValidatedShader;
+use hyphae::scene::post::{AudioFeature, Parameter, PostEffect};
+use hyphae::scene::primitives::PrimitiveType::Quad;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+
+const POST_COMMON_SHADER: &str = include_str!("../../shaders/post_common.wgsl");
+
+///The scene's post effects, drawn as fullscreen quads over the rendered frame once it is stored in the history.
+///Effects render in stages; every stage reads the previous stage's output and the effect's input,
+///bound after the resource groups, and writes to one of the two textures the effect isn't reading from.
+pub struct PostProcessing {
+    graphics: Rc<GraphicsDevice>,
+    effects: Vec<PostEffectProgram>,
+    quad: MeshBuffers,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    ///Receives a copy of the rendered frame; the other two are ping-ponged between stages
+    targets: [PostTarget; 3],
+    stages: Vec<PostStage>,
+}
+
+impl PostProcessing {
+    pub fn new(
+        graphics: Rc<GraphicsDevice>,
+        effects: &[PostEffect],
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> anyhow::Result<Self> {
+        log::debug!(
+            "Creating post processing chain of {} effects",
+            effects.len()
+        );
+        let filter = Self::filter(&graphics);
+        let bind_group_layout = Self::create_bind_group_layout(&graphics.device, filter);
+        let mut pipeline_bind_group_layouts = bind_group_layouts.to_vec();
+        pipeline_bind_group_layouts.push(&bind_group_layout);
+        let pipeline_layout =
+            graphics
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Post Effect Pipeline Layout"),
+                    bind_group_layouts: pipeline_bind_group_layouts.as_slice(),
+                    push_constant_ranges: &[],
+                });
+        let effects = effects
+            .iter()
+            .map(|effect| PostEffectProgram::new(&graphics, effect, &pipeline_layout))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let quad = MeshBuffers::from_primitive(&graphics.device, &Quad);
+        let sampler = graphics.device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: filter,
+            min_filter: filter,
+            ..Default::default()
+        });
+        let targets = Self::create_targets(&graphics);
+        let mut post_processing = Self {
+            graphics,
+            effects,
+            quad,
+            sampler,
+            bind_group_layout,
+            targets,
+            stages: Vec::new(),
+        };
+        post_processing.create_stages();
+        Ok(post_processing)
+    }
+
+    ///Copies the rendered frame from `output` and runs every stage, the last one drawing back into `output_view`
+    pub fn encode(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_groups: &[&wgpu::BindGroup],
+        output: &wgpu::Texture,
+        output_view: &wgpu::TextureView,
+    ) {
+        log::trace!("Encoding post processing");
+        encoder.copy_texture_to_texture(
+            output.as_image_copy(),
+            self.targets[0].texture.as_image_copy(),
+            self.graphics.create_render_extent3d(1),
+        );
+        for stage in &self.stages {
+            let view = match stage.target {
+                Some(target) => &self.targets[target].texture_view,
+                None => output_view,
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Effect Render Pass"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&self.effects[stage.effect].pipeline);
+            let mut bind_group_index = 0;
+            for bind_group in bind_groups {
+                render_pass.set_bind_group(bind_group_index, bind_group, &[]);
+                bind_group_index += 1;
+            }
+            render_pass.set_bind_group(bind_group_index, &stage.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.quad.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(self.quad.index_buffer.slice(..), self.quad.index_format);
+            render_pass.draw_indexed(0..self.quad.index_count, 0, 0..1);
+        }
+    }
+
+    pub fn resize(&mut self) {
+        log::trace!("Resizing post processing");
+        self.targets = Self::create_targets(&self.graphics);
+        self.create_stages();
+    }
+
+    ///Assigns every stage its input and output texture, the last stage writes to the output
+    fn create_stages(&mut self) {
+        let mut stages = Vec::new();
+        let mut source = 0;
+        let stage_count: u32 = self.effects.iter().map(|effect| effect.stage_count).sum();
+        for (effect_index, effect) in self.effects.iter().enumerate() {
+            let free: Vec<usize> = (0..3).filter(|target| *target != source).collect();
+            let mut input = source;
+            for stage in 0..effect.stage_count {
+                let target = if stages.len() as u32 + 1 == stage_count {
+                    None
+                } else {
+                    Some(free[stage as usize % 2])
+                };
+                stages.push(PostStage {
+                    effect: effect_index,
+                    target,
+                    bind_group: self.create_stage_bind_group(effect, stage, input, source),
+                });
+                input = target.unwrap_or(input);
+            }
+            source = input;
+        }
+        self.stages = stages;
+    }
+
+    fn create_stage_bind_group(
+        &self,
+        effect: &PostEffectProgram,
+        stage: u32,
+        input: usize,
+        source: usize,
+    ) -> wgpu::BindGroup {
+        let info_buffer =
+            self.graphics
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Post Stage Info Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[PostStageInfo {
+                        parameters: effect.parameters,
+                        stage,
+                        stage_count: effect.stage_count,
+                        padding: [0; 2],
+                    }]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        self.graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post stage bind group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(
+                            &self.targets[input].texture_view,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(
+                            &self.targets[source].texture_view,
+                        ),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: info_buffer.as_entire_binding(),
+                    },
+                ],
+            })
+    }
+
+    ///Float textures are only filterable if `filter` is linear
+    fn create_bind_group_layout(
+        device: &wgpu::Device,
+        filter: wgpu::FilterMode,
+    ) -> wgpu::BindGroupLayout {
+        let filterable = filter == wgpu::FilterMode::Linear;
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+            },
+            count: None,
+        };
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post stage bind group layout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        comparison: false,
+                        filtering: filterable,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    ///Blurs rely on bilinear filtering, 32 bit float textures aren't filterable without an optional feature
+    fn filter(graphics: &GraphicsDevice) -> wgpu::FilterMode {
+        if graphics.get_internal_format() == wgpu::TextureFormat::Rgba32Float {
+            wgpu::FilterMode::Nearest
+        } else {
+            wgpu::FilterMode::Linear
+        }
+    }
+
+    fn create_targets(graphics: &GraphicsDevice) -> [PostTarget; 3] {
+        [
+            PostTarget::new(graphics, "Post Input Texture"),
+            PostTarget::new(graphics, "Post Ping Texture"),
+            PostTarget::new(graphics, "Post Pong Texture"),
+        ]
+    }
+}
+
+///Pipeline and parameters of one effect, `parameters` are (value, feature, amount, unused)
+struct PostEffectProgram {
+    pipeline: wgpu::RenderPipeline,
+    parameters: [[f32; 4]; 4],
+    stage_count: u32,
+}
+
+impl PostEffectProgram {
+    fn new(
+        graphics: &GraphicsDevice,
+        effect: &PostEffect,
+        pipeline_layout: &wgpu::PipelineLayout,
+    ) -> anyhow::Result<Self> {
+        log::debug!("Creating post effect {:?}", effect);
+        let (fragment_source, parameters, stage_count) = Self::describe(effect);
+        let validated_source = format!("{}{}", POST_COMMON_SHADER, fragment_source).validated()?;
+        let pipeline = graphics.capture_errors(|| {
+            let module = graphics
+                .device
+                .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                    label: Some("Post Effect Shader Module"),
+                    source: wgpu::ShaderSource::Wgsl(validated_source.into()),
+                });
+            graphics
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: Some("Post Effect Pipeline"),
+                    layout: Some(pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &module,
+                        entry_point: "main",
+                        buffers: &[Vertex::desc()],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &module,
+                        entry_point: "main",
+                        targets: &[wgpu::ColorTargetState {
+                            format: graphics.get_internal_format(),
+                            blend: None,
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }],
+                    }),
+                    primitive: wgpu::PrimitiveState {
+                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        strip_index_format: None,
+                        front_face: wgpu::FrontFace::Cw,
+                        cull_mode: Some(wgpu::Face::Back),
+                        polygon_mode: wgpu::PolygonMode::Fill,
+                        clamp_depth: false,
+                        conservative: false,
+                    },
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState {
+                        count: 1,
+                        mask: !0,
+                        alpha_to_coverage_enabled: false,
+                    },
+                })
+        })?;
+        Ok(Self {
+            pipeline,
+            parameters: parameters.map(Self::encode_parameter),
+            stage_count,
+        })
+    }
+
+    ///Fragment shader, parameters in shader order and number of stages of an effect
+    fn describe(effect: &PostEffect) -> (&'static str, [Parameter; 4], u32) {
+        let unused = Parameter::Constant(0.0);
+        match effect {
+            PostEffect::Bloom(bloom) => (
+                include_str!("../../shaders/post_bloom.wgsl"),
+                [bloom.threshold, bloom.intensity, bloom.radius, unused],
+                4,
+            ),
+            PostEffect::GaussianBlur(blur) => (
+                include_str!("../../shaders/post_gaussian_blur.wgsl"),
+                [blur.radius, unused, unused, unused],
+                2,
+            ),
+            PostEffect::KawaseBlur(blur) => (
+                include_str!("../../shaders/post_kawase_blur.wgsl"),
+                [blur.offset, unused, unused, unused],
+                blur.iterations.max(1),
+            ),
+            PostEffect::ChromaticAberration(aberration) => (
+                include_str!("../../shaders/post_chromatic_aberration.wgsl"),
+                [aberration.strength, unused, unused, unused],
+                1,
+            ),
+            PostEffect::Vignette(vignette) => (
+                include_str!("../../shaders/post_vignette.wgsl"),
+                [vignette.intensity, vignette.smoothness, unused, unused],
+                1,
+            ),
+            PostEffect::FilmGrain(grain) => (
+                include_str!("../../shaders/post_film_grain.wgsl"),
+                [grain.intensity, unused, unused, unused],
+                1,
+            ),
+            PostEffect::Pixelate(pixelate) => (
+                include_str!("../../shaders/post_pixelate.wgsl"),
+                [pixelate.size, unused, unused, unused],
+                1,
+            ),
+            PostEffect::Kaleidoscope(kaleidoscope) => (
+                include_str!("../../shaders/post_kaleidoscope.wgsl"),
+                [kaleidoscope.segments, kaleidoscope.rotation, unused, unused],
+                1,
+            ),
+            PostEffect::ColorGrading(grading) => (
+                include_str!("../../shaders/post_color_grading.wgsl"),
+                [
+                    grading.exposure,
+                    grading.contrast,
+                    grading.saturation,
+                    grading.hue_shift,
+                ],
+                1,
+            ),
+        }
+    }
+
+    ///Feature indices match the constants in `post_common.wgsl`
+    fn encode_parameter(parameter: Parameter) -> [f32; 4] {
+        let (value, feature, amount) = parameter.parts();
+        let feature = match feature {
+            AudioFeature::None => 0.0,
+            AudioFeature::Level => 1.0,
+            AudioFeature::Bass => 2.0,
+            AudioFeature::Mid => 3.0,
+            AudioFeature::Treble => 4.0,
+        };
+        [value, feature, amount, 0.0]
+    }
+}
+
+struct PostStage {
+    effect: usize,
+    ///Index into the post targets, `None` for the output
+    target: Option<usize>,
+    bind_group: wgpu::BindGroup,
+}
+
+struct PostTarget {
+    texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+}
+
+impl PostTarget {
+    fn new(graphics: &GraphicsDevice, label: &str) -> Self {
+        log::trace!("Creating post target {}", label);
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: graphics.create_render_extent3d(1),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: graphics.get_internal_format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST,
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            texture,
+            texture_view,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostStageInfo {
+    parameters: [[f32; 4]; 4],
+    stage: u32,
+    stage_count: u32,
+    padding: [u32; 2],
+}
@@ -0,0 +1,679 @@
+use crate::validation::{validate_module, CubensisValidatedShader};
+use hyphae::configuration::library::LibraryConfiguration;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::scene::shaders::ShaderLanguage;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+///Name of the generated resource declarations, included with `#include <cubensis>`
+pub const PRELUDE_MODULE: &str = "cubensis";
+
+///Name of the shader wrapped by the Shadertoy wrapper, included there with `#include <image>`
+const SHADERTOY_IMAGE_MODULE: &str = "image";
+
+///Name of the wrapper in diagnostics
+const SHADERTOY_WRAPPER_FILE: &str = "<shadertoy>";
+
+///GLSL render shader calling the `mainImage` of Shadertoy shaders
+const SHADERTOY_WRAPPER: &str = include_str!("../../shaders/shadertoy.glsl");
+
+///Library path of a shader and the stage it was loaded for, if it is GLSL
+type ShaderLoad = (String, Option<naga::ShaderStage>);
+
+///Resolves `#include "path"`, `#define NAME value`, `#undef`, `#ifdef`, `#ifndef`, `#else` and `#endif`
+///in shaders before they are validated. Includes are relative to the scene library and pasted once per shader.
+///In GLSL, `#if`, `#elif`, function-like macros and the defines within `#if` blocks are left to the GLSL frontend.
+pub struct ShaderPreprocessor {
+    defines: HashMap<String, String>,
+    ///Source of the `#include <cubensis>` module in WGSL
+    wgsl_prelude: Rc<str>,
+    ///Source of the `#include <cubensis>` module in GLSL
+    glsl_prelude: Rc<str>,
+    ///Canonical paths of the files every shader included when it was last loaded, by library path and GLSL stage
+    includes: RefCell<HashMap<ShaderLoad, HashSet<PathBuf>>>,
+}
+
+impl ShaderPreprocessor {
+    ///`defines` are set before the first line of every shader, usually from the scene file
+    pub fn new(
+        defines: HashMap<String, String>,
+        wgsl_prelude: String,
+        glsl_prelude: String,
+    ) -> Self {
+        log::debug!("Creating shader preprocessor with defines {:?}", defines);
+        Self {
+            defines,
+            wgsl_prelude: Rc::from(wgsl_prelude),
+            glsl_prelude: Rc::from(glsl_prelude),
+            includes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    ///Loads the shader at `library_path` as the source of the wgpu module running its `stage`.
+    ///GLSL is preprocessed with `VERTEX`, `FRAGMENT` or `COMPUTE` defined for `stage` and compiled to SPIR-V,
+    ///Shadertoy shaders likewise once wrapped. WGSL and SPIR-V modules hold every stage.
+    ///Errors carry a `ShaderDiagnostic`.
+    pub fn compile(
+        &self,
+        library_path: &str,
+        language: ShaderLanguage,
+        stage: naga::ShaderStage,
+    ) -> anyhow::Result<wgpu::ShaderSource<'static>> {
+        log::debug!(
+            "Compiling {:?} shader {} for the {:?} stage",
+            language,
+            library_path,
+            stage
+        );
+        match language {
+            ShaderLanguage::Wgsl => Ok(wgpu::ShaderSource::Wgsl(
+                self.load(library_path)?.validated()?.source.into(),
+            )),
+            ShaderLanguage::Shadertoy if stage == naga::ShaderStage::Compute => {
+                Err(ShaderDiagnostic::unlocated(
+                    library_path,
+                    "Shadertoy shaders can only be render shaders".to_string(),
+                )
+                .into())
+            }
+            ShaderLanguage::Glsl | ShaderLanguage::Shadertoy => Ok(wgpu::ShaderSource::SpirV(
+                self.preprocess(library_path, language, Some(stage))?
+                    .compiled_glsl(stage)?
+                    .into(),
+            )),
+            ShaderLanguage::SpirV => Ok(wgpu::ShaderSource::SpirV(
+                Self::load_spirv(library_path)?.into(),
+            )),
+        }
+    }
+
+    ///Reads and preprocesses the WGSL shader at `library_path`, recording the files it includes.
+    ///Errors carry a `ShaderDiagnostic`.
+    pub fn load(&self, library_path: &str) -> anyhow::Result<PreprocessedShader> {
+        self.preprocess(library_path, ShaderLanguage::Wgsl, None)
+    }
+
+    ///True if the shader at `library_path` included `path` when it was last loaded
+    pub fn includes(&self, library_path: &str, path: &Path) -> bool {
+        let path = match path.canonicalize() {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+        self.includes
+            .borrow()
+            .iter()
+            .any(|((shader, _), included)| shader == library_path && included.contains(&path))
+    }
+
+    fn preprocess(
+        &self,
+        library_path: &str,
+        language: ShaderLanguage,
+        stage: Option<naga::ShaderStage>,
+    ) -> anyhow::Result<PreprocessedShader> {
+        log::debug!("Preprocessing shader {}", library_path);
+        let mut defines = self.defines.clone();
+        if let Some(stage) = stage {
+            defines.insert(Self::stage_define(stage).to_string(), String::new());
+        }
+        let prelude = if language.is_glsl() {
+            &self.glsl_prelude
+        } else {
+            &self.wgsl_prelude
+        };
+        let mut state = PreprocessorState {
+            defines,
+            prelude: prelude.clone(),
+            included: HashSet::new(),
+            includes_prelude: false,
+            shader: PreprocessedShader {
+                shader: library_path.to_string(),
+                language,
+                source: String::new(),
+                lines: Vec::new(),
+            },
+        };
+        let result = match language {
+            ShaderLanguage::Shadertoy => state.process_source(
+                Rc::from(SHADERTOY_WRAPPER_FILE),
+                LibraryConfiguration::scene_library_path().join(SHADERTOY_WRAPPER_FILE),
+                SHADERTOY_WRAPPER,
+            ),
+            _ => state.process_file(library_path),
+        };
+        //recorded even if preprocessing failed, so fixing an include reloads the shader
+        self.includes
+            .borrow_mut()
+            .insert((library_path.to_string(), stage), state.included);
+        result.map(|_| state.shader)
+    }
+
+    fn stage_define(stage: naga::ShaderStage) -> &'static str {
+        match stage {
+            naga::ShaderStage::Vertex => "VERTEX",
+            naga::ShaderStage::Fragment => "FRAGMENT",
+            naga::ShaderStage::Compute => "COMPUTE",
+        }
+    }
+
+    ///Reads the precompiled SPIR-V shader at `library_path` and checks it the way wgpu will read it
+    fn load_spirv(library_path: &str) -> anyhow::Result<Vec<u32>> {
+        let path = LibraryConfiguration::scene_library_path().join(library_path);
+        let bytes = std::fs::read(&path).map_err(|error| {
+            ShaderDiagnostic::unlocated(
+                library_path,
+                format!("Failed to read shader {}: {}", library_path, error),
+            )
+        })?;
+        //wgpu panics on SPIR-V it can't parse
+        let options = naga::front::spv::Options {
+            adjust_coordinate_space: false,
+            strict_capabilities: true,
+            block_ctx_dump_prefix: None,
+        };
+        let module = naga::front::spv::parse_u8_slice(&bytes, &options).map_err(|error| {
+            ShaderDiagnostic::unlocated(library_path, format!("Invalid SPIR-V: {}", error))
+        })?;
+        validate_module(&module)
+            .map_err(|error| ShaderDiagnostic::unlocated(library_path, error_chain(&error)))?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect())
+    }
+}
+
+///Preprocessed shader source, remembering where every line came from
+pub struct PreprocessedShader {
+    ///Library path of the shader
+    pub shader: String,
+    pub language: ShaderLanguage,
+    pub source: String,
+    lines: Vec<SourceLine>,
+}
+
+impl PreprocessedShader {
+    ///File and line in it of the 1-based `line` of the preprocessed source
+    pub fn locate(&self, line: usize) -> Option<&SourceLine> {
+        self.lines.get(line.checked_sub(1)?)
+    }
+
+    ///Diagnostic at `line` and `column` of the preprocessed source
+    fn diagnostic(&self, line: usize, column: usize, message: String) -> ShaderDiagnostic {
+        let text = self
+            .source
+            .lines()
+            .nth(line.saturating_sub(1))
+            .unwrap_or("");
+        match self.locate(line) {
+            Some(source_line) => ShaderDiagnostic {
+                shader: self.shader.clone(),
+                file: source_line.file.to_string(),
+                path: LibraryConfiguration::scene_library_path().join(&*source_line.file),
+                line: source_line.line,
+                column,
+                snippet: ShaderDiagnostic::label_snippet(source_line.line, column, text, &message),
+                message,
+            },
+            None => ShaderDiagnostic::unlocated(&self.shader, message),
+        }
+    }
+
+    ///Diagnostic at the byte `offset` into the preprocessed source
+    fn diagnostic_at(&self, offset: usize, message: String) -> ShaderDiagnostic {
+        let preceding = self.source.get(..offset).unwrap_or(&self.source);
+        let line_start = preceding.rfind('\n').map_or(0, |position| position + 1);
+        self.diagnostic(
+            preceding.matches('\n').count() + 1,
+            preceding[line_start..].chars().count() + 1,
+            message,
+        )
+    }
+
+    ///Validation errors have no spans, they point at the offending function where possible
+    fn validation_diagnostic(&self, error: &naga::valid::ValidationError) -> ShaderDiagnostic {
+        let function = match error {
+            naga::valid::ValidationError::Function { name, .. }
+            | naga::valid::ValidationError::EntryPoint { name, .. } => self.find_function(name),
+            _ => None,
+        };
+        match function {
+            Some((line, column)) => self.diagnostic(line, column, error_chain(error)),
+            None => ShaderDiagnostic::unlocated(&self.shader, error_chain(error)),
+        }
+    }
+
+    ///Line and column of the name in the declaration of the function `name`, if it is found
+    fn find_function(&self, name: &str) -> Option<(usize, usize)> {
+        let declaration = match self.language {
+            ShaderLanguage::Glsl | ShaderLanguage::Shadertoy => format!(" {}(", name),
+            _ => format!("fn {}(", name),
+        };
+        let name_offset = declaration.len() - name.len() - 1;
+        self.source.lines().enumerate().find_map(|(index, text)| {
+            text.find(&declaration).map(|position| {
+                (
+                    index + 1,
+                    text[..position + name_offset].chars().count() + 1,
+                )
+            })
+        })
+    }
+
+    ///Validates the GLSL source and compiles its `stage` entry point to SPIR-V
+    fn compiled_glsl(&self, stage: naga::ShaderStage) -> anyhow::Result<Vec<u32>> {
+        log::debug!("Compiling GLSL shader {}", self.shader);
+        let options = naga::front::glsl::Options {
+            stage,
+            defines: Default::default(),
+        };
+        let module = naga::front::glsl::Parser::default()
+            .parse(&options, &self.source)
+            .map_err(|errors| match errors.first() {
+                Some(error) => match error.meta.to_range() {
+                    Some(range) => self.diagnostic_at(range.start, error.to_string()),
+                    None => ShaderDiagnostic::unlocated(&self.shader, error.to_string()),
+                },
+                None => {
+                    ShaderDiagnostic::unlocated(&self.shader, "Failed to parse GLSL".to_string())
+                }
+            })?;
+        let info = validate_module(&module).map_err(|error| self.validation_diagnostic(&error))?;
+        let mut options = naga::back::spv::Options::default();
+        //wgpu reads SPIR-V without adjusting the coordinate space back
+        options
+            .flags
+            .remove(naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE);
+        naga::back::spv::write_vec(&module, &info, &options, None).map_err(|error| {
+            ShaderDiagnostic::unlocated(&self.shader, format!("Failed to write SPIR-V: {}", error))
+                .into()
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SourceLine {
+    ///Library path of the file
+    pub file: Rc<str>,
+    ///1-based line number
+    pub line: usize,
+}
+
+impl CubensisValidatedShader for PreprocessedShader {
+    fn validated(self) -> anyhow::Result<Self> {
+        log::debug!("Validating preprocessed shader {}", self.shader);
+        let module = naga::front::wgsl::parse_str(&self.source).map_err(|error| {
+            let (line, column) = error.location(&self.source);
+            self.diagnostic(line, column, error.to_string())
+        })?;
+        validate_module(&module).map_err(|error| self.validation_diagnostic(&error))?;
+        Ok(self)
+    }
+}
+
+struct PreprocessorState {
+    defines: HashMap<String, String>,
+    prelude: Rc<str>,
+    included: HashSet<PathBuf>,
+    includes_prelude: bool,
+    shader: PreprocessedShader,
+}
+
+impl PreprocessorState {
+    fn process_file(&mut self, library_path: &str) -> anyhow::Result<()> {
+        let path = LibraryConfiguration::scene_library_path().join(library_path);
+        let unreadable = |error: std::io::Error| ShaderDiagnostic {
+            file: library_path.to_string(),
+            path: path.clone(),
+            ..ShaderDiagnostic::unlocated(
+                &self.shader.shader,
+                format!("Failed to read shader {}: {}", library_path, error),
+            )
+        };
+        let source = std::fs::read_to_string(&path).map_err(unreadable)?;
+        if !self
+            .included
+            .insert(path.canonicalize().map_err(unreadable)?)
+        {
+            log::trace!("Skipping repeated include of {}", library_path);
+            return Ok(());
+        }
+        self.process_source(Rc::from(library_path), path, &source)
+    }
+
+    ///Processes the `source` of `file`, found at `path`
+    fn process_source(&mut self, file: Rc<str>, path: PathBuf, source: &str) -> anyhow::Result<()> {
+        let shader = self.shader.shader.clone();
+        let is_glsl = self.shader.language.is_glsl();
+        let error = |line: usize, text: &str, message: String| {
+            Err(ShaderDiagnostic {
+                shader: shader.clone(),
+                file: file.to_string(),
+                path: path.clone(),
+                line,
+                column: 1,
+                snippet: ShaderDiagnostic::label_snippet(line, 1, text, &message),
+                message,
+            }
+            .into())
+        };
+        //whether the lines in each enclosing conditional are emitted, `None` for those left to the GLSL frontend
+        let mut conditionals: Vec<Option<bool>> = Vec::new();
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let is_active = conditionals.iter().all(|active| active.unwrap_or(true));
+            let directive = match text.trim_start().strip_prefix('#') {
+                Some(directive) => directive.trim(),
+                None => {
+                    if is_active {
+                        self.emit(text, &file, line);
+                    }
+                    continue;
+                }
+            };
+            let (keyword, argument) = match directive.split_once(char::is_whitespace) {
+                Some((keyword, argument)) => (keyword, argument.trim()),
+                None => (directive, ""),
+            };
+            match keyword {
+                "ifdef" | "ifndef" => {
+                    let is_defined = self.defines.contains_key(argument);
+                    conditionals.push(Some(is_defined == (keyword == "ifdef")));
+                }
+                "if" if is_glsl => {
+                    conditionals.push(None);
+                    if is_active {
+                        self.emit(text, &file, line);
+                    }
+                }
+                "elif" if is_glsl && conditionals.last() == Some(&None) => {
+                    if is_active {
+                        self.emit(text, &file, line);
+                    }
+                }
+                "else" => match conditionals.last_mut() {
+                    Some(Some(active)) => *active = !*active,
+                    Some(None) => {
+                        if is_active {
+                            self.emit(text, &file, line);
+                        }
+                    }
+                    None => return error(line, text, "#else without #ifdef".to_string()),
+                },
+                "endif" => match conditionals.pop() {
+                    Some(Some(_)) => {}
+                    Some(None) => {
+                        if is_active {
+                            self.emit(text, &file, line);
+                        }
+                    }
+                    None => return error(line, text, "#endif without #ifdef".to_string()),
+                },
+                _ if !is_active => {}
+                "define" => {
+                    let (name, value) = match argument.split_once(char::is_whitespace) {
+                        Some((name, value)) => (name, value.trim()),
+                        None => (argument, ""),
+                    };
+                    if name.is_empty() {
+                        return error(line, text, "#define without a name".to_string());
+                    }
+                    //function-like macros and defines depending on `#if` are left to the GLSL frontend
+                    if is_glsl && (name.contains('(') || conditionals.contains(&None)) {
+                        self.emit(text, &file, line);
+                    } else {
+                        self.defines.insert(name.to_string(), value.to_string());
+                    }
+                }
+                "undef" => {
+                    if self.defines.remove(argument).is_none() && is_glsl {
+                        self.emit(text, &file, line);
+                    }
+                }
+                "include"
+                    if argument == format!("<{}>", SHADERTOY_IMAGE_MODULE)
+                        && self.shader.language == ShaderLanguage::Shadertoy =>
+                {
+                    let image = self.shader.shader.clone();
+                    self.process_file(&image)?;
+                }
+                "include" if argument == format!("<{}>", PRELUDE_MODULE) => {
+                    if !self.includes_prelude {
+                        self.includes_prelude = true;
+                        let prelude = self.prelude.clone();
+                        let module: Rc<str> = Rc::from(argument);
+                        for (index, text) in prelude.lines().enumerate() {
+                            self.emit(text, &module, index + 1);
+                        }
+                    }
+                }
+                "include" => {
+                    let include_path = argument
+                        .strip_prefix('"')
+                        .and_then(|argument| argument.strip_suffix('"'));
+                    match include_path {
+                        Some(include_path) => self.process_file(include_path)?,
+                        None => {
+                            return error(line, text, format!("malformed #include {}", argument));
+                        }
+                    }
+                }
+                //left to the GLSL frontend
+                "version" | "extension" | "pragma" if is_glsl => {
+                    self.emit(text, &file, line);
+                }
+                _ => return error(line, text, format!("unknown directive #{}", keyword)),
+            }
+        }
+        if !conditionals.is_empty() {
+            let line = source.lines().count();
+            let text = source.lines().last().unwrap_or("");
+            return error(line, text, "missing #endif".to_string());
+        }
+        Ok(())
+    }
+
+    ///Appends a source line with its defines substituted
+    fn emit(&mut self, text: &str, file: &Rc<str>, line: usize) {
+        if self.defines.is_empty() {
+            self.shader.source.push_str(text);
+        } else {
+            let substituted = self.substitute(text, &mut Vec::new());
+            self.shader.source.push_str(&substituted);
+        }
+        self.shader.source.push('\n');
+        self.shader.lines.push(SourceLine {
+            file: file.clone(),
+            line,
+        });
+    }
+
+    ///`text` with its defines substituted, including those in the substituted values,
+    ///except the defines already being substituted in `expanding`
+    fn substitute(&self, text: &str, expanding: &mut Vec<String>) -> String {
+        let mut substituted = String::with_capacity(text.len());
+        let mut identifier = String::new();
+        for character in text.chars().map(Some).chain(std::iter::once(None)) {
+            if let Some(character) = character.filter(|c| c.is_alphanumeric() || *c == '_') {
+                identifier.push(character);
+                continue;
+            }
+            match self.defines.get(&identifier) {
+                Some(value) if !expanding.contains(&identifier) => {
+                    expanding.push(std::mem::take(&mut identifier));
+                    substituted.push_str(&self.substitute(value, expanding));
+                    expanding.pop();
+                }
+                _ => substituted.push_str(&identifier),
+            }
+            identifier.clear();
+            substituted.extend(character);
+        }
+        substituted
+    }
+}
+
+///The message of `error` followed by those of its sources
+fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocess(
+        language: ShaderLanguage,
+        defines: &[(&str, &str)],
+        source: &str,
+    ) -> anyhow::Result<PreprocessedShader> {
+        let mut state = PreprocessorState {
+            defines: defines
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            prelude: Rc::from(""),
+            included: HashSet::new(),
+            includes_prelude: false,
+            shader: PreprocessedShader {
+                shader: "test".to_string(),
+                language,
+                source: String::new(),
+                lines: Vec::new(),
+            },
+        };
+        state.process_source(Rc::from("test"), PathBuf::from("test"), source)?;
+        Ok(state.shader)
+    }
+
+    fn preprocessed_source(
+        language: ShaderLanguage,
+        defines: &[(&str, &str)],
+        source: &str,
+    ) -> String {
+        preprocess(language, defines, source).unwrap().source
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        let source = "#ifdef A\n\
+                      a\n\
+                      #ifdef B\n\
+                      a_b\n\
+                      #else\n\
+                      a_not_b\n\
+                      #endif\n\
+                      #else\n\
+                      not_a\n\
+                      #ifndef B\n\
+                      not_a_not_b\n\
+                      #endif\n\
+                      #endif\n\
+                      end";
+        let output =
+            |defines: &[(&str, &str)]| preprocessed_source(ShaderLanguage::Wgsl, defines, source);
+        assert_eq!(output(&[("A", "")]), "a\na_not_b\nend\n");
+        assert_eq!(output(&[("A", ""), ("B", "")]), "a\na_b\nend\n");
+        assert_eq!(output(&[]), "not_a\nnot_a_not_b\nend\n");
+        assert_eq!(output(&[("B", "")]), "not_a\nend\n");
+    }
+
+    #[test]
+    fn unbalanced_conditionals() {
+        assert!(preprocess(ShaderLanguage::Wgsl, &[], "#ifdef A\na").is_err());
+        assert!(preprocess(ShaderLanguage::Wgsl, &[], "a\n#endif").is_err());
+        assert!(preprocess(ShaderLanguage::Wgsl, &[], "#else").is_err());
+    }
+
+    #[test]
+    fn nested_defines() {
+        let source = "#define SIZE (COUNT * 2)\n\
+                      #define COUNT 4\n\
+                      let size = SIZE; let count = COUNT; let size_2 = SIZE_2;";
+        assert_eq!(
+            preprocessed_source(ShaderLanguage::Wgsl, &[], source),
+            "let size = (4 * 2); let count = 4; let size_2 = SIZE_2;\n"
+        );
+    }
+
+    #[test]
+    fn self_referencing_defines() {
+        let source = "#define A A + 1\n\
+                      #define B C\n\
+                      #define C B\n\
+                      A B C";
+        assert_eq!(
+            preprocessed_source(ShaderLanguage::Wgsl, &[], source),
+            "A + 1 B C\n"
+        );
+    }
+
+    #[test]
+    fn if_is_left_to_glsl_frontend() {
+        let source = "#if VALUE > 1\n\
+                      #define LARGE 1\n\
+                      #elif VALUE\n\
+                      #define SQUARE(x) ((x) * (x))\n\
+                      #else\n\
+                      #ifdef VALUE\n\
+                      defined\n\
+                      #endif\n\
+                      #endif\n\
+                      VALUE";
+        assert_eq!(
+            preprocessed_source(ShaderLanguage::Glsl, &[("VALUE", "2")], source),
+            "#if 2 > 1\n\
+             #define LARGE 1\n\
+             #elif 2\n\
+             #define SQUARE(x) ((x) * (x))\n\
+             #else\n\
+             defined\n\
+             #endif\n\
+             2\n"
+        );
+        assert!(preprocess(ShaderLanguage::Wgsl, &[], source).is_err());
+    }
+
+    #[test]
+    fn diagnostic_at_maps_to_source_lines() {
+        let source = "#define VALUE 1.0\n\
+                      fn helper() {}\n\
+                      #ifdef MISSING\n\
+                      fn missing() {}\n\
+                      #endif\n\
+                      let é = VALUE + oops;";
+        let shader = preprocess(ShaderLanguage::Wgsl, &[], source).unwrap();
+        let offset = shader.source.find("oops").unwrap();
+        let diagnostic = shader.diagnostic_at(offset, "unknown identifier".to_string());
+        assert_eq!(diagnostic.file, "test");
+        assert_eq!((diagnostic.line, diagnostic.column), (6, 15));
+    }
+
+    #[test]
+    fn find_function_locates_declarations() {
+        let wgsl = preprocess(
+            ShaderLanguage::Wgsl,
+            &[],
+            "fn helper() {}\n\n  fn main() {}",
+        )
+        .unwrap();
+        assert_eq!(wgsl.find_function("main"), Some((3, 6)));
+        assert_eq!(wgsl.find_function("missing"), None);
+        let glsl = preprocess(
+            ShaderLanguage::Glsl,
+            &[],
+            "#version 450\nfloat helper(float x) { return x; }\nvoid main() {}",
+        )
+        .unwrap();
+        assert_eq!(glsl.find_function("helper"), Some((2, 7)));
+        assert_eq!(glsl.find_function("main"), Some((3, 6)));
+    }
+}
@@ -8,7 +8,7 @@ pub struct DepthTexture {
 }
 
 impl DepthTexture {
-    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    pub fn bind_group_layout(device: &wgpu::Device, multisampled: bool) -> wgpu::BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Depth buffer bind group layout"),
             entries: &[
@@ -16,9 +16,12 @@ impl DepthTexture {
                     binding: 0,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
-                        multisampled: false,
+                        multisampled,
                         view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        //multisampled textures can only be loaded, not filtered
+                        sample_type: wgpu::TextureSampleType::Float {
+                            filterable: !multisampled,
+                        },
                     },
                     count: None,
                 },
@@ -34,16 +37,18 @@ impl DepthTexture {
             ],
         })
     }
-    pub fn new(device: &wgpu::Device, size: wgpu::Extent3d) -> Self {
-        log::trace!("Creating Depth Buffer");
+    pub fn new(device: &wgpu::Device, size: wgpu::Extent3d, sample_count: u32) -> Self {
+        log::trace!("Creating Depth Buffer with {} samples", sample_count);
         let descriptor = wgpu::TextureDescriptor {
             label: Some("Depth Buffer"),
             size,
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: crate::DEPTH_BUFFER_FORMAT,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
         };
         let texture = device.create_texture(&descriptor);
         let texture_view = texture.create_view(&Default::default());
@@ -59,7 +64,7 @@ impl DepthTexture {
             lod_max_clamp: 100.0,
             ..Default::default()
         });
-        let bind_group_layout = Self::bind_group_layout(device);
+        let bind_group_layout = Self::bind_group_layout(device, sample_count > 1);
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Depth buffer bind group"),
             layout: &bind_group_layout,
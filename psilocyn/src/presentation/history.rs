@@ -0,0 +1,283 @@
+use crate::device::GraphicsDevice;
+use std::rc::Rc;
+use wgpu::util::DeviceExt;
+
+///Previously rendered frames, stored as the layers of a single array texture used as a ring buffer.
+///Frame `n` frames ago (`n >= 1`) is layer `(offset + n - 1) % depth`.
+///A history of one frame is bound as a plain 2D texture, the way it was before its depth was configurable.
+pub struct RenderHistory {
+    graphics: Rc<GraphicsDevice>,
+    depth: u32,
+    head: u32,
+    texture: wgpu::Texture,
+    history_buffer: wgpu::Buffer,
+    depth_history: DepthHistory,
+    expose_depth: bool,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl RenderHistory {
+    pub fn bind_group_layout(device: &wgpu::Device, depth: u32) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Render history bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: Self::view_dimension(depth),
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        comparison: false,
+                        filtering: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    ///`expose_depth` enables copying depth into the bound depth textures, which are placeholders otherwise
+    pub fn new(graphics: Rc<GraphicsDevice>, depth: u32, expose_depth: bool) -> Self {
+        let depth = Self::clamp_depth(&graphics, depth);
+        log::debug!("Creating render history with {} layers", depth);
+        let texture = Self::create_texture(&graphics, depth);
+        let depth_history = DepthHistory::new(&graphics, expose_depth);
+        let prior_depth_view = depth_history
+            .prior_depth
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let previous_depth_view = depth_history
+            .previous_depth
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(Self::view_dimension(depth)),
+            ..Default::default()
+        });
+        let sampler = graphics.device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            ..Default::default()
+        });
+        let history_buffer =
+            graphics
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Render History Uniform Buffer"),
+                    contents: bytemuck::cast_slice(&[HistoryBufferData { depth, offset: 0 }]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+        let bind_group_layout = Self::bind_group_layout(&graphics.device, depth);
+        let bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Render history bind group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: history_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::TextureView(&prior_depth_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(&previous_depth_view),
+                    },
+                ],
+            });
+        Self {
+            graphics,
+            depth,
+            head: 0,
+            texture,
+            history_buffer,
+            depth_history,
+            expose_depth,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+
+    fn view_dimension(depth: u32) -> wgpu::TextureViewDimension {
+        if depth == 1 {
+            wgpu::TextureViewDimension::D2
+        } else {
+            wgpu::TextureViewDimension::D2Array
+        }
+    }
+
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    pub fn expose_depth(&self) -> bool {
+        self.expose_depth
+    }
+
+    ///Targets of the depth copies, if depth is exposed to shaders
+    pub fn depth_history(&self) -> Option<&DepthHistory> {
+        if self.expose_depth {
+            Some(&self.depth_history)
+        } else {
+            None
+        }
+    }
+
+    ///Publishes the ring offset for the frame about to be rendered
+    pub fn start_frame(&self) {
+        self.graphics.queue.write_buffer(
+            &self.history_buffer,
+            0,
+            bytemuck::cast_slice(&[HistoryBufferData {
+                depth: self.depth,
+                offset: self.head,
+            }]),
+        );
+    }
+
+    ///Copies the rendered frame into the oldest layer, which becomes the most recent one
+    pub fn encode_copy(&mut self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::Texture) {
+        log::trace!("Storing frame in render history");
+        self.head = (self.head + self.depth - 1) % self.depth;
+        let size = self.graphics.create_render_extent3d(1);
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: source,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: self.head,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            size,
+        );
+    }
+
+    fn create_texture(graphics: &GraphicsDevice, depth: u32) -> wgpu::Texture {
+        graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render History Texture"),
+            size: graphics.create_render_extent3d(depth),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: graphics.get_internal_format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        })
+    }
+
+    fn clamp_depth(graphics: &GraphicsDevice, depth: u32) -> u32 {
+        let max_depth = graphics.device.limits().max_texture_array_layers;
+        let clamped_depth = depth.clamp(1, max_depth);
+        if clamped_depth != depth {
+            log::warn!(
+                "History depth {} is out of range, using {} instead",
+                depth,
+                clamped_depth
+            );
+        }
+        clamped_depth
+    }
+}
+
+///Sampleable copies of the depth buffer shared by all passes rendering at the output size
+pub struct DepthHistory {
+    ///Depth of the current frame before the draw that is sampling it
+    pub prior_depth: wgpu::Texture,
+    ///Depth of the previous frame once all passes finished
+    pub previous_depth: wgpu::Texture,
+}
+
+impl DepthHistory {
+    fn new(graphics: &GraphicsDevice, expose_depth: bool) -> Self {
+        log::trace!("Creating depth history");
+        //unused depth textures are only bound, so they don't need to match the output
+        let size = if expose_depth {
+            graphics.create_render_extent3d(1)
+        } else {
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            }
+        };
+        let create_texture = |label| {
+            graphics.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: crate::DEPTH_BUFFER_FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            })
+        };
+        Self {
+            prior_depth: create_texture("Prior Depth Texture"),
+            previous_depth: create_texture("Previous Depth Texture"),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct HistoryBufferData {
+    depth: u32,
+    offset: u32,
+}
@@ -1,104 +1,129 @@
+use crate::capture::FrameCapture;
 use crate::device::GraphicsDevice;
-use crate::gui::{CubensisGuiApp, CubensisGuiRenderer, GuiHost};
+use crate::gui::{CubensisGuiApp, CubensisGuiRenderer, GuiHost, GuiRenderTarget};
 use crate::mesh::buffers::{MeshBuffers, Vertex};
-use crate::presentation::depth_texture::DepthTexture;
 use crate::resources::CubensisResourceCollection;
+use history::{DepthHistory, RenderHistory};
+use hyphae::configuration::graphics::PresentationFilter;
 use hyphae::configuration::library::Library;
-use hyphae::scene::geometry::GeometrySource;
 use hyphae::scene::primitives::PrimitiveType::Quad;
 use std::rc::Rc;
 use textures::PresentTexture;
+use tonemapping::TonemappingUniform;
 
-mod depth_texture;
+pub(crate) mod depth_texture;
+pub mod history;
 pub mod presenter;
 mod textures;
+mod tonemapping;
 
-pub struct PresentationPass<const HISTORY_DEPTH: usize> {
-    render_history: Vec<PresentTexture>,
+pub struct PresentationPass {
+    output: PresentTexture,
+    render_history: RenderHistory,
     graphics: Rc<GraphicsDevice>,
     buffers: MeshBuffers,
+    tonemapping: TonemappingUniform,
     pipeline: wgpu::RenderPipeline,
-    depth_texture: DepthTexture,
 }
 
-impl<const HISTORY_DEPTH: usize> PresentationPass<HISTORY_DEPTH> {
-    pub fn new(graphics: Rc<GraphicsDevice>) -> Self {
+impl PresentationPass {
+    pub fn new(graphics: Rc<GraphicsDevice>, history_depth: u32, expose_depth: bool) -> Self {
         log::debug!("Creating presentation pass");
-        let size = graphics.create_extent3d(1);
-        let format = graphics.get_format();
-
-        let render_history = Self::create_textures(&graphics.device, size, format);
-        let buffers = MeshBuffers::new(&graphics.device, &GeometrySource::Primitive(Quad));
-        let pipeline = Self::create_pipeline(&graphics.device, graphics.get_format());
-        let depth_texture = DepthTexture::new(&graphics.device, size);
+        let output = Self::create_output(&graphics);
+        let render_history = RenderHistory::new(graphics.clone(), history_depth, expose_depth);
+        let buffers = MeshBuffers::from_primitive(&graphics.device, &Quad);
+        let tonemapping = TonemappingUniform::new(&graphics);
+        let pipeline = Self::create_pipeline(
+            &graphics.device,
+            graphics.get_format(),
+            Self::filter(&graphics),
+            &tonemapping.bind_group_layout,
+        );
 
         Self {
+            output,
             render_history,
             graphics,
             buffers,
+            tonemapping,
             pipeline,
-            depth_texture,
         }
     }
 
     pub fn get_current_bind_group(&self) -> &wgpu::BindGroup {
-        &self.render_history[1].bind_group
+        &self.render_history.bind_group
     }
 
     pub fn get_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
-        &self.render_history[0].bind_group_layout
+        &self.render_history.bind_group_layout
+    }
+
+    pub fn get_history_depth(&self) -> u32 {
+        self.render_history.depth()
     }
 
-    pub fn get_depth_texture_view(&self) -> &wgpu::TextureView {
-        &self.depth_texture.texture_view
+    pub fn get_depth_history(&self) -> Option<&DepthHistory> {
+        self.render_history.depth_history()
+    }
+
+    ///Replaces the render history, which starts out blank. Pipelines have to be rebuilt against the
+    ///new bind group layout when the depth changes from or to a single frame.
+    pub fn set_history(&mut self, history_depth: u32, expose_depth: bool) {
+        log::debug!(
+            "Setting history depth to {}, depth exposed: {}",
+            history_depth,
+            expose_depth
+        );
+        self.render_history =
+            RenderHistory::new(self.graphics.clone(), history_depth, expose_depth);
     }
 
     pub fn start_frame(&mut self) {
-        self.rotate_textures();
+        self.render_history.start_frame();
     }
 
     pub fn create_presentation_view(&self) -> &wgpu::TextureView {
         log::trace!("Creating presentation pass view");
-        &self.render_history[0].texture_view
+        &self.output.texture_view
+    }
+
+    pub fn get_output_texture(&self) -> &wgpu::Texture {
+        &self.output.texture
     }
-    pub fn resize<'b>(&mut self) {
+
+    ///Appends the rendered frame to the history, must follow all rendering into the presentation view
+    ///except for post processing, which only applies to the presented frame
+    pub fn store_history(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.render_history
+            .encode_copy(encoder, &self.output.texture);
+    }
+
+    pub fn resize(&mut self) {
         log::trace!("Resizing presentation pass");
-        let size = self.graphics.create_extent3d(1);
-        let format = self.graphics.get_format();
-        self.pipeline = Self::create_pipeline(&self.graphics.device, self.graphics.get_format());
-        self.render_history = Self::create_textures(&self.graphics.device, size, format);
-        self.depth_texture = DepthTexture::new(&self.graphics.device, size);
-    }
-
-    fn create_pipeline(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("Presentation bind group layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                    ty: wgpu::BindingType::Sampler {
-                        comparison: false,
-                        filtering: false,
-                    },
-                    count: None,
-                },
-            ],
-        });
+        self.pipeline = Self::create_pipeline(
+            &self.graphics.device,
+            self.graphics.get_format(),
+            Self::filter(&self.graphics),
+            &self.tonemapping.bind_group_layout,
+        );
+        self.output = Self::create_output(&self.graphics);
+        self.set_history(
+            self.render_history.depth(),
+            self.render_history.expose_depth(),
+        );
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        filter: wgpu::FilterMode,
+        tonemapping_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let bind_group_layout = PresentTexture::bind_group_layout(device, filter);
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[&bind_group_layout],
+                bind_group_layouts: &[&bind_group_layout, tonemapping_bind_group_layout],
                 push_constant_ranges: &[],
             });
         let module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
@@ -151,68 +176,101 @@ impl<const HISTORY_DEPTH: usize> PresentationPass<HISTORY_DEPTH> {
         gui: &mut Gui,
         library: &Library,
         resource_collection: &ResourceCollection,
+        capture: &mut FrameCapture,
     ) -> Result<(), wgpu::SurfaceError> {
         log::trace!("Presenting image");
         let surface_texture = self.graphics.get_current_texture()?;
         let surface_view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Presentation Render Pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &surface_view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.01,
-                            g: 0.01,
-                            b: 0.01,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            });
-            render_pass.set_pipeline(&self.pipeline);
-            render_pass.set_bind_group(0, &self.render_history[0].bind_group, &[]);
-            render_pass.set_vertex_buffer(0, self.buffers.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(
-                self.buffers.index_buffer.slice(..),
-                wgpu::IndexFormat::Uint16,
-            );
-            render_pass.draw_indexed(0..self.buffers.index_count, 0, 0..1);
+        self.encode_presentation(&mut encoder, &surface_view);
+        let capture_targets = capture.begin_frame();
+        for capture_target in &capture_targets {
+            self.encode_presentation(&mut encoder, capture_target.texture_view());
+        }
+        let mut gui_targets = vec![GuiRenderTarget {
+            view: &surface_view,
+            size: self.graphics.create_extent3d(1),
+        }];
+        gui_targets.extend(
+            capture_targets
+                .iter()
+                .filter(|capture_target| capture_target.include_gui())
+                .map(|capture_target| GuiRenderTarget {
+                    view: capture_target.texture_view(),
+                    size: capture_target.size(),
+                }),
+        );
+        encoder.render_gui(gui_host, gui, library, resource_collection, &gui_targets);
+        for capture_target in &capture_targets {
+            capture_target.copy_to_readback(&mut encoder);
         }
-        encoder.render_gui(gui_host, gui, library, resource_collection, &surface_view);
         self.graphics
             .queue
             .submit(std::iter::once(encoder.finish()));
+        capture.end_frame(capture_targets);
         surface_texture.present();
         Ok(())
     }
-    fn rotate_textures(&mut self) {
-        log::trace!("Rotating history images");
-        self.render_history.rotate_right(1);
+
+    ///Draws the most recent history texture into the given view, tonemapped into its format
+    pub(crate) fn encode_presentation(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+    ) {
+        log::trace!("Encoding presentation pass");
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Presentation Render Pass"),
+            color_attachments: &[wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 0.01,
+                        g: 0.01,
+                        b: 0.01,
+                        a: 1.0,
+                    }),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.output.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.tonemapping.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.buffers.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(
+            self.buffers.index_buffer.slice(..),
+            self.buffers.index_format,
+        );
+        render_pass.draw_indexed(0..self.buffers.index_count, 0, 0..1);
     }
 
-    fn create_textures(
-        device: &wgpu::Device,
-        size: wgpu::Extent3d,
-        format: wgpu::TextureFormat,
-    ) -> Vec<PresentTexture> {
-        log::debug!("Creating {} presentation pass textures", HISTORY_DEPTH + 1);
-        let mut res = Vec::new();
-        for i in 0..(HISTORY_DEPTH + 1) as u32 {
-            res.push(PresentTexture::new(
-                device,
-                size,
-                format,
-                wgpu::TextureDimension::D2,
-                format!("Render History Texture {:?}", i).as_str(),
-            ));
+    fn create_output(graphics: &GraphicsDevice) -> PresentTexture {
+        log::debug!("Creating presentation pass output texture");
+        PresentTexture::new(
+            &graphics.device,
+            graphics.create_render_extent3d(1),
+            graphics.get_internal_format(),
+            wgpu::TextureDimension::D2,
+            Self::filter(graphics),
+            "Presentation Output Texture",
+        )
+    }
+
+    ///Filter scaling the rendered frame to the presentation target
+    fn filter(graphics: &GraphicsDevice) -> wgpu::FilterMode {
+        match graphics.presentation_filter {
+            PresentationFilter::Nearest => wgpu::FilterMode::Nearest,
+            //32 bit float textures aren't filterable without an optional feature
+            PresentationFilter::Linear
+                if graphics.get_internal_format() == wgpu::TextureFormat::Rgba32Float =>
+            {
+                wgpu::FilterMode::Nearest
+            }
+            PresentationFilter::Linear => wgpu::FilterMode::Linear,
         }
-        log::debug!("Created {} presentation pass textures", res.len());
-        res
     }
 }
@@ -10,7 +10,12 @@ pub struct PresentTexture {
 }
 
 impl PresentTexture {
-    pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    ///Float textures are only filterable if `filter` is linear
+    pub fn bind_group_layout(
+        device: &wgpu::Device,
+        filter: wgpu::FilterMode,
+    ) -> wgpu::BindGroupLayout {
+        let filterable = filter == wgpu::FilterMode::Linear;
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Presentation pass texture bind group layout"),
             entries: &[
@@ -20,7 +25,7 @@ impl PresentTexture {
                     ty: wgpu::BindingType::Texture {
                         multisampled: false,
                         view_dimension: wgpu::TextureViewDimension::D2,
-                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        sample_type: wgpu::TextureSampleType::Float { filterable },
                     },
                     count: None,
                 },
@@ -29,7 +34,7 @@ impl PresentTexture {
                     visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Sampler {
                         comparison: false,
-                        filtering: false,
+                        filtering: filterable,
                     },
                     count: None,
                 },
@@ -41,6 +46,7 @@ impl PresentTexture {
         size: wgpu::Extent3d,
         format: wgpu::TextureFormat,
         dimension: wgpu::TextureDimension,
+        filter: wgpu::FilterMode,
         label: &str,
     ) -> Self {
         log::trace!("Creating Presentation Texture");
@@ -62,9 +68,11 @@ impl PresentTexture {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: filter,
+            min_filter: filter,
             ..Default::default()
         });
-        let bind_group_layout = Self::bind_group_layout(device);
+        let bind_group_layout = Self::bind_group_layout(device, filter);
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Presentation pass texture bind group"),
             layout: &bind_group_layout,
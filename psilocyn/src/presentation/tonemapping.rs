@@ -0,0 +1,77 @@
+use crate::device::GraphicsDevice;
+use hyphae::configuration::graphics::{TonemapOperator, Tonemapping};
+use wgpu::util::DeviceExt;
+
+///Tonemapping parameters of the presentation shader, bound at group 1
+pub struct TonemappingUniform {
+    _buffer: wgpu::Buffer,
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl TonemappingUniform {
+    pub fn new(graphics: &GraphicsDevice) -> Self {
+        log::debug!("Creating tonemapping uniform {:?}", graphics.tonemapping);
+        let buffer = graphics
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Tonemapping Uniform Buffer"),
+                contents: bytemuck::cast_slice(&[TonemappingData::from(&graphics.tonemapping)]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let bind_group_layout =
+            graphics
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Tonemapping bind group layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Tonemapping bind group"),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            });
+        Self {
+            _buffer: buffer,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+}
+
+///`operator` matches the constants in `present_shader.wgsl`
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct TonemappingData {
+    operator: u32,
+    exposure: f32,
+    padding: [u32; 2],
+}
+
+impl From<&Tonemapping> for TonemappingData {
+    fn from(tonemapping: &Tonemapping) -> Self {
+        Self {
+            operator: match tonemapping.operator {
+                TonemapOperator::None => 0,
+                TonemapOperator::Reinhard => 1,
+                TonemapOperator::Aces => 2,
+            },
+            exposure: tonemapping.exposure,
+            padding: [0; 2],
+        }
+    }
+}
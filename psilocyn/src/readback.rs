@@ -0,0 +1,109 @@
+use image::RgbaImage;
+
+const BYTES_PER_PIXEL: u32 = 4;
+
+///A mappable buffer that colour textures can be copied into and read back on the CPU
+pub struct TextureReadback {
+    buffer: wgpu::Buffer,
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+    padded_bytes_per_row: u32,
+}
+
+impl TextureReadback {
+    pub fn new(device: &wgpu::Device, size: wgpu::Extent3d, format: wgpu::TextureFormat) -> Self {
+        log::trace!("Creating texture readback buffer");
+        let unpadded_bytes_per_row = size.width * BYTES_PER_PIXEL;
+        let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Texture Readback Buffer"),
+            size: (padded_bytes_per_row * size.height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            size,
+            format,
+            padded_bytes_per_row,
+        }
+    }
+
+    pub fn size(&self) -> wgpu::Extent3d {
+        self.size
+    }
+
+    pub fn copy_from(&self, encoder: &mut wgpu::CommandEncoder, texture: &wgpu::Texture) {
+        log::trace!("Copying texture into readback buffer");
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(self.padded_bytes_per_row),
+                    rows_per_image: std::num::NonZeroU32::new(self.size.height),
+                },
+            },
+            wgpu::Extent3d {
+                depth_or_array_layers: 1,
+                ..self.size
+            },
+        );
+    }
+
+    ///Blocks until all submitted copies have completed, then returns the buffer contents as RGBA
+    pub fn read(&self, device: &wgpu::Device) -> anyhow::Result<RgbaImage> {
+        log::trace!("Reading back texture");
+        let mapping = self.map();
+        device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(mapping)?;
+        self.read_mapped()
+    }
+
+    ///Starts mapping the buffer; the returned future resolves once the device has been polled past the copy
+    pub fn map(
+        &self,
+    ) -> impl std::future::Future<Output = Result<(), wgpu::BufferAsyncError>> + Send {
+        log::trace!("Mapping texture readback buffer");
+        self.buffer.slice(..).map_async(wgpu::MapMode::Read)
+    }
+
+    ///Returns the contents of a buffer whose mapping has completed and unmaps it
+    pub fn read_mapped(&self) -> anyhow::Result<RgbaImage> {
+        log::trace!("Reading mapped texture readback buffer");
+        let image = self.to_image(&self.buffer.slice(..).get_mapped_range());
+        self.buffer.unmap();
+        image
+    }
+
+    fn to_image(&self, padded_data: &[u8]) -> anyhow::Result<RgbaImage> {
+        let unpadded_bytes_per_row = (self.size.width * BYTES_PER_PIXEL) as usize;
+        let mut data = Vec::with_capacity(unpadded_bytes_per_row * self.size.height as usize);
+        for row in padded_data.chunks(self.padded_bytes_per_row as usize) {
+            data.extend_from_slice(&row[..unpadded_bytes_per_row]);
+        }
+        match self.format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {}
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                for pixel in data.chunks_mut(BYTES_PER_PIXEL as usize) {
+                    pixel.swap(0, 2);
+                }
+            }
+            format => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unsupported readback format {:?}",
+                    format
+                )))
+            }
+        }
+        RgbaImage::from_raw(self.size.width, self.size.height, data)
+            .ok_or_else(|| anyhow::Error::msg("Readback buffer size doesn't match image size"))
+    }
+}
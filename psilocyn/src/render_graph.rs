@@ -0,0 +1,994 @@
+use crate::device::GraphicsDevice;
+use crate::mesh::compute::CubensisMeshComputePass;
+use crate::mesh::instances::CubensisInstanceComputePass;
+use crate::mesh::particles::CubensisParticleComputePass;
+use crate::mesh::transform::MeshTransforms;
+use crate::mesh::{CubensisMeshRenderPass, Mesh, PassLayout};
+use crate::post::PostProcessing;
+use crate::preprocessor::ShaderPreprocessor;
+use crate::presentation::depth_texture::DepthTexture;
+use crate::presentation::history::DepthHistory;
+use crate::resources::CubensisResourceCollection;
+use hyphae::configuration::library::LibraryConfiguration;
+use hyphae::diagnostics::ShaderDiagnostic;
+use hyphae::scene::render_graph::{
+    RenderPassDescriptor, RenderTargetDescriptor, RenderTargetFormat, OUTPUT_TARGET,
+};
+use hyphae::scene::shaders::{LoadOperation, PassOperations};
+use hyphae::scene::transform::Transform;
+use hyphae::scene::Scene;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+///Name under which a failure to build the post effects of the scene is reported in the diagnostics
+const POST_EFFECTS_DIAGNOSTIC: &str = "post_effects";
+
+///The render targets and passes of a scene.
+///Passes run in declaration order, except that a pass always runs after every pass writing one of its inputs.
+pub struct RenderGraph {
+    graphics: Rc<GraphicsDevice>,
+    targets: HashMap<String, GraphTarget>,
+    depth_textures: HashMap<[u32; 2], DepthTexture>,
+    ///Multisampled attachments resolving into each pass output, by output name
+    multisampled_targets: HashMap<String, MultisampledTarget>,
+    passes: Vec<GraphPass>,
+    transforms: Rc<MeshTransforms>,
+    preprocessor: Rc<ShaderPreprocessor>,
+    sample_count: u32,
+    ///Present if the scene has post effects, which are drawn over the output after it was stored in the history
+    post_processing: Option<PostProcessing>,
+    ///Latest build error of every shader that failed to build, by library path
+    diagnostics: BTreeMap<String, ShaderDiagnostic>,
+}
+
+impl RenderGraph {
+    pub fn new<ResourceCollection: CubensisResourceCollection>(
+        graphics: Rc<GraphicsDevice>,
+        scene: &Scene,
+        resource_collection: &ResourceCollection,
+        history_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<Self> {
+        log::debug!("Creating render graph for scene {}", scene.name);
+        let output_size = Self::output_size(&graphics);
+        let mut targets = HashMap::new();
+        for descriptor in &scene.render_graph.targets {
+            if descriptor.name == OUTPUT_TARGET || targets.contains_key(&descriptor.name) {
+                return Err(anyhow::Error::msg(format!(
+                    "Render target name {} is reserved or declared twice",
+                    descriptor.name
+                )));
+            }
+            let target = GraphTarget::new(&graphics, descriptor.clone(), output_size);
+            targets.insert(descriptor.name.clone(), target);
+        }
+        let pass_descriptors = Self::pass_descriptors(scene);
+        let formats: Vec<_> = std::iter::once(graphics.get_internal_format())
+            .chain(targets.values().map(|target| target.format))
+            .collect();
+        let sample_count = graphics.sample_count(scene.sample_count, &formats);
+        //multisampled depth can't be copied into the single sampled depth history
+        if sample_count > 1 && scene.expose_depth {
+            return Err(anyhow::Error::msg(format!(
+                "Scene {} exposes depth, which multisampled rendering doesn't support, set its sample_count to 1",
+                scene.name
+            )));
+        }
+        //every mesh drawn by a pass gets its own transform slot
+        let transforms = Rc::new(MeshTransforms::new(
+            &graphics,
+            pass_descriptors
+                .iter()
+                .map(|descriptor| descriptor.meshes.len())
+                .sum(),
+        ));
+        let preprocessor = Rc::new(ShaderPreprocessor::new(
+            scene.shader_defines.clone(),
+            ResourceCollection::wgsl_prelude(),
+            ResourceCollection::glsl_prelude(),
+        ));
+        //inputs and color formats are filled in by each pass
+        let shared_layout = PassLayout {
+            history_bind_group_layout,
+            input_bind_group_layout: None,
+            color_formats: &[],
+            mesh_transforms: &transforms,
+            sample_count,
+            preprocessor: &preprocessor,
+        };
+        let mut passes = Vec::new();
+        for index in Self::schedule(&pass_descriptors)? {
+            let descriptor = pass_descriptors[index].clone();
+            passes.push(GraphPass::new(
+                &graphics,
+                descriptor,
+                scene,
+                &targets,
+                resource_collection,
+                &shared_layout,
+            )?);
+        }
+        let mut diagnostics = BTreeMap::new();
+        let post_processing = if scene.post_effects.is_empty() {
+            None
+        } else {
+            match PostProcessing::new(
+                graphics.clone(),
+                &scene.post_effects,
+                resource_collection.get_bind_group_layouts().as_slice(),
+            ) {
+                Ok(post_processing) => Some(post_processing),
+                Err(error) => {
+                    log::warn!("Presenting frames without post effects: {:#}", error);
+                    let diagnostic = ShaderDiagnostic::from_error(POST_EFFECTS_DIAGNOSTIC, &error);
+                    diagnostics.insert(diagnostic.shader.clone(), diagnostic);
+                    None
+                }
+            }
+        };
+        let mut render_graph = Self {
+            graphics,
+            targets,
+            depth_textures: HashMap::new(),
+            multisampled_targets: HashMap::new(),
+            passes,
+            transforms,
+            preprocessor,
+            sample_count,
+            post_processing,
+            diagnostics,
+        };
+        render_graph.collect_build_errors();
+        render_graph.create_depth_textures();
+        render_graph.create_multisampled_targets();
+        Ok(render_graph)
+    }
+
+    ///Draws every pass; `output_view` receives the passes writing to the output target.
+    ///Each render shader draws in its own render pass so its load/store operations apply to it alone.
+    ///With `depth_history`, output sized passes copy their depth there for later draws and frames.
+    pub fn encode(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_groups: &[&wgpu::BindGroup],
+        history_bind_group: &wgpu::BindGroup,
+        output_view: &wgpu::TextureView,
+        depth_history: Option<&DepthHistory>,
+    ) {
+        log::trace!("Encoding render graph");
+        self.encode_geometry(encoder, bind_groups);
+        let output_size = Self::output_size(&self.graphics);
+        for pass in &self.passes {
+            let mut is_first_draw = true;
+            for mesh in &pass.meshes {
+                for (shader_index, render_shader) in
+                    mesh.mesh_descriptor.render_shaders.iter().enumerate()
+                {
+                    let operations = mesh.mesh_descriptor.pass_operations(render_shader);
+                    let loads_depth =
+                        PassOperations::resolve_load(operations.depth_load, is_first_draw)
+                            == LoadOperation::Load;
+                    if let (Some(depth_history), true) = (depth_history, loads_depth) {
+                        if pass.size == output_size {
+                            self.copy_depth(encoder, pass.size, &depth_history.prior_depth);
+                        }
+                    }
+                    let mut renderpass = self.begin_render_pass(
+                        encoder,
+                        pass,
+                        operations,
+                        is_first_draw,
+                        output_view,
+                    );
+                    if mesh.is_compute_generated() {
+                        renderpass.draw_mesh_shader_indirect(
+                            mesh,
+                            shader_index,
+                            bind_groups,
+                            history_bind_group,
+                            pass.input_bind_group.as_ref(),
+                        );
+                    } else {
+                        renderpass.draw_mesh_shader_indexed(
+                            mesh,
+                            shader_index,
+                            bind_groups,
+                            history_bind_group,
+                            pass.input_bind_group.as_ref(),
+                        );
+                    }
+                    is_first_draw = false;
+                }
+            }
+            if is_first_draw {
+                //passes without draws still clear their outputs
+                self.begin_render_pass(
+                    encoder,
+                    pass,
+                    &PassOperations::default(),
+                    true,
+                    output_view,
+                );
+            }
+        }
+        if let Some(depth_history) = depth_history {
+            if self.depth_textures.contains_key(&output_size) {
+                self.copy_depth(encoder, output_size, &depth_history.previous_depth);
+            }
+        }
+    }
+
+    ///Applies the scene's post effects to the rendered frame in `output`, after it was stored in the history
+    pub fn encode_post_processing(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_groups: &[&wgpu::BindGroup],
+        output: &wgpu::Texture,
+        output_view: &wgpu::TextureView,
+    ) {
+        if let Some(post_processing) = &self.post_processing {
+            post_processing.encode(encoder, bind_groups, output, output_view);
+        }
+    }
+
+    ///Schedules a simulation step for every particle system
+    pub fn update(&mut self, time_delta: std::time::Duration) {
+        for mesh in self
+            .passes
+            .iter_mut()
+            .flat_map(|pass| pass.meshes.iter_mut())
+        {
+            mesh.update(time_delta);
+        }
+    }
+
+    ///Replaces the transform of every mesh named `mesh_name`, in all passes drawing it
+    pub fn set_mesh_transform(
+        &mut self,
+        mesh_name: &str,
+        transform: Transform,
+    ) -> anyhow::Result<()> {
+        log::debug!("Setting transform of mesh {} to {:?}", mesh_name, transform);
+        let mut found = false;
+        for mesh in self
+            .passes
+            .iter_mut()
+            .flat_map(|pass| pass.meshes.iter_mut())
+            .filter(|mesh| mesh.mesh_descriptor.name == mesh_name)
+        {
+            mesh.set_transform(transform);
+            found = true;
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(anyhow::Error::msg(format!(
+                "No loaded mesh is named {}",
+                mesh_name
+            )))
+        }
+    }
+
+    ///Regenerates compute geometry and simulates particles before any pass draws them
+    fn encode_geometry(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        bind_groups: &[&wgpu::BindGroup],
+    ) {
+        let mut meshes = self
+            .passes
+            .iter()
+            .flat_map(|pass| pass.meshes.iter())
+            .filter(|mesh| mesh.requires_compute_pass())
+            .peekable();
+        if meshes.peek().is_none() {
+            return;
+        }
+        log::trace!("Encoding geometry compute pass");
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Geometry Compute Pass"),
+        });
+        for mesh in meshes {
+            compute_pass.generate_mesh_geometry(mesh, bind_groups);
+            compute_pass.simulate_particles(mesh, bind_groups);
+            compute_pass.generate_mesh_instances(mesh, bind_groups);
+        }
+    }
+
+    fn copy_depth(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        size: [u32; 2],
+        destination: &wgpu::Texture,
+    ) {
+        log::trace!("Copying depth buffer");
+        let [width, height] = size;
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.depth_textures[&size].texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: destination,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    fn begin_render_pass<'a>(
+        &'a self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        pass: &'a GraphPass,
+        operations: &PassOperations,
+        is_first_draw: bool,
+        output_view: &'a wgpu::TextureView,
+    ) -> wgpu::RenderPass<'a> {
+        let color_attachments: Vec<wgpu::RenderPassColorAttachment> = pass
+            .descriptor
+            .outputs
+            .iter()
+            .map(|output| {
+                let view = match self.targets.get(output) {
+                    Some(target) => &target.texture_view,
+                    None => output_view,
+                };
+                match self.multisampled_targets.get(output) {
+                    Some(multisampled_target) => wgpu::RenderPassColorAttachment {
+                        view: &multisampled_target.texture_view,
+                        resolve_target: Some(view),
+                        ops: operations.color_operations(is_first_draw),
+                    },
+                    None => wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: operations.color_operations(is_first_draw),
+                    },
+                }
+            })
+            .collect();
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(pass.descriptor.name.as_str()),
+            color_attachments: color_attachments.as_slice(),
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_textures[&pass.size].texture_view,
+                depth_ops: Some(operations.depth_operations(is_first_draw)),
+                stencil_ops: None,
+            }),
+        })
+    }
+
+    ///Recreates targets that follow the output size, along with the bind groups sampling them
+    pub fn resize(&mut self) {
+        log::trace!("Resizing render graph");
+        let output_size = Self::output_size(&self.graphics);
+        for target in self.targets.values_mut() {
+            *target = GraphTarget::new(&self.graphics, target.descriptor.clone(), output_size);
+        }
+        for pass in self.passes.iter_mut() {
+            pass.size = GraphPass::resolve_size(&pass.descriptor, &self.targets, output_size);
+            pass.input_bind_group = pass.input_bind_group_layout.as_ref().map(|layout| {
+                GraphPass::create_input_bind_group(
+                    &self.graphics,
+                    layout,
+                    &pass.descriptor,
+                    &self.targets,
+                )
+            });
+        }
+        self.create_depth_textures();
+        self.create_multisampled_targets();
+        if let Some(post_processing) = self.post_processing.as_mut() {
+            post_processing.resize();
+        }
+        self.collect_build_errors();
+    }
+
+    ///Moves the errors of shaders that failed to build with their meshes into `shader_diagnostics`.
+    ///Failed render shaders are drawn with the error pipeline and failed compute shaders are skipped until they are fixed.
+    fn collect_build_errors(&mut self) {
+        let build_errors = self
+            .passes
+            .iter_mut()
+            .flat_map(|pass| pass.meshes.iter_mut())
+            .flat_map(Mesh::take_build_errors);
+        for diagnostic in build_errors {
+            self.diagnostics
+                .insert(diagnostic.shader.clone(), diagnostic);
+        }
+    }
+
+    ///Rebuilds the pipelines of every shader loaded from or including `path` and reloads meshes loaded from it.
+    ///Shaders failing to build keep their previous pipelines and meshes failing to load keep their previous geometry,
+    ///their errors are kept in `shader_diagnostics`.
+    pub fn try_hot_reload<ResourceCollection: CubensisResourceCollection>(
+        &mut self,
+        path: &PathBuf,
+        resource_collection: &ResourceCollection,
+        history_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> anyhow::Result<()> {
+        log::debug!("Attempting hot reload of shader at path {:?}", path);
+        let preprocessor = &self.preprocessor;
+        let diagnostics = &mut self.diagnostics;
+        //shaders also reload when a file they include changes
+        let is_dependency = |library_path: &str| {
+            Self::is_library_path(library_path, path) || preprocessor.includes(library_path, path)
+        };
+        let mut reloaded = false;
+        let mut failed_builds = 0;
+        for pass in self.passes.iter_mut() {
+            let pass_layout = PassLayout {
+                history_bind_group_layout,
+                input_bind_group_layout: pass.input_bind_group_layout.as_ref(),
+                color_formats: pass.color_formats.as_slice(),
+                mesh_transforms: &self.transforms,
+                sample_count: self.sample_count,
+                preprocessor,
+            };
+            for mesh in pass.meshes.iter_mut() {
+                if let Some(compute_shader_path) = mesh.compute_shader_path().map(String::from) {
+                    if is_dependency(&compute_shader_path) {
+                        log::debug!("Rebuilding geometry compute shader {}", compute_shader_path);
+                        let result = mesh.rebuild_geometry(resource_collection);
+                        if !Self::record_build(diagnostics, &[&compute_shader_path], result) {
+                            failed_builds += 1;
+                        }
+                        reloaded = true;
+                    }
+                }
+                if let Some(geometry_file_path) = mesh.geometry_file_path().map(String::from) {
+                    if Self::is_library_path(&geometry_file_path, path) {
+                        log::debug!("Reloading mesh file {}", geometry_file_path);
+                        let result = mesh.reload_geometry();
+                        if !Self::record_build(diagnostics, &[&geometry_file_path], result) {
+                            failed_builds += 1;
+                        }
+                        reloaded = true;
+                    }
+                }
+                if let Some([emit_shader_path, update_shader_path]) = mesh
+                    .particle_shader_paths()
+                    .map(|paths| paths.map(String::from))
+                {
+                    if is_dependency(&emit_shader_path) || is_dependency(&update_shader_path) {
+                        log::debug!("Rebuilding particle shaders");
+                        let result = mesh.rebuild_particles(resource_collection);
+                        if !Self::record_build(
+                            diagnostics,
+                            &[&emit_shader_path, &update_shader_path],
+                            result,
+                        ) {
+                            failed_builds += 1;
+                        }
+                        reloaded = true;
+                    }
+                }
+                if let Some(instance_shader_path) = mesh.instance_shader_path().map(String::from) {
+                    if is_dependency(&instance_shader_path) {
+                        log::debug!(
+                            "Rebuilding instance compute shader {}",
+                            instance_shader_path
+                        );
+                        let result = mesh.rebuild_instances(resource_collection);
+                        if !Self::record_build(diagnostics, &[&instance_shader_path], result) {
+                            failed_builds += 1;
+                        }
+                        reloaded = true;
+                    }
+                }
+                let shaders_to_update: Vec<_> = mesh
+                    .mesh_descriptor
+                    .render_shaders
+                    .iter()
+                    .filter(|s| is_dependency(&s.path))
+                    .cloned()
+                    .collect();
+                for render_shader in shaders_to_update {
+                    log::debug!("Rebuilding shader named {}", render_shader.name);
+                    let shader_path = render_shader.path.clone();
+                    let result = mesh.rebuild(render_shader, resource_collection, &pass_layout);
+                    if !Self::record_build(diagnostics, &[&shader_path], result) {
+                        failed_builds += 1;
+                    }
+                    reloaded = true;
+                }
+            }
+        }
+        if failed_builds > 0 {
+            Err(anyhow::Error::msg(format!(
+                "{} shader(s) or mesh file(s) failed to build",
+                failed_builds
+            )))
+        } else if reloaded {
+            Ok(())
+        } else {
+            Err(anyhow::Error::msg(
+                "Updated path doesn't correspond to any loaded meshes/shaders",
+            ))
+        }
+    }
+
+    ///Latest build error of every shader that currently fails to build
+    pub fn shader_diagnostics(&self) -> Vec<ShaderDiagnostic> {
+        self.diagnostics.values().cloned().collect()
+    }
+
+    ///Keeps the error of a failed build of the shaders at `shader_paths`, or clears their previous errors.
+    ///Returns true if the build succeeded.
+    fn record_build(
+        diagnostics: &mut BTreeMap<String, ShaderDiagnostic>,
+        shader_paths: &[&str],
+        result: anyhow::Result<()>,
+    ) -> bool {
+        for shader_path in shader_paths {
+            diagnostics.remove(*shader_path);
+        }
+        match result {
+            Ok(()) => true,
+            Err(error) => {
+                let diagnostic = ShaderDiagnostic::from_error(shader_paths[0], &error);
+                log::warn!("Failed to build shader {}", diagnostic);
+                diagnostics.insert(diagnostic.shader.clone(), diagnostic);
+                false
+            }
+        }
+    }
+
+    ///True if `library_path`, relative to the scene library, points to `path`
+    fn is_library_path(library_path: &str, path: &Path) -> bool {
+        let shader_path = LibraryConfiguration::scene_library_path().join(library_path);
+        let res = match (shader_path.canonicalize(), path.canonicalize()) {
+            (Ok(shader_path), Ok(path)) => shader_path == path,
+            _ => false,
+        };
+        log::debug!("Checking ({}) for path {:?}", &res, &shader_path);
+        res
+    }
+
+    ///The scene's passes, or a single pass drawing every mesh into the output
+    fn pass_descriptors(scene: &Scene) -> Vec<RenderPassDescriptor> {
+        if !scene.render_graph.passes.is_empty() {
+            return scene.render_graph.passes.clone();
+        }
+        vec![RenderPassDescriptor {
+            name: "Main Pass".to_string(),
+            meshes: scene.meshes.iter().map(|mesh| mesh.name.clone()).collect(),
+            inputs: Vec::new(),
+            outputs: vec![OUTPUT_TARGET.to_string()],
+        }]
+    }
+
+    ///Orders passes so that producers of a target run before its consumers, keeping declaration order otherwise
+    fn schedule(passes: &[RenderPassDescriptor]) -> anyhow::Result<Vec<usize>> {
+        let dependencies: Vec<Vec<usize>> = passes
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                passes
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_index, other)| {
+                        *other_index != index
+                            && other
+                                .outputs
+                                .iter()
+                                .any(|output| pass.inputs.contains(output))
+                    })
+                    .map(|(other_index, _)| other_index)
+                    .collect()
+            })
+            .collect();
+        let mut order: Vec<usize> = Vec::with_capacity(passes.len());
+        while order.len() < passes.len() {
+            let next = (0..passes.len()).find(|index| {
+                !order.contains(index)
+                    && dependencies[*index]
+                        .iter()
+                        .all(|dependency| order.contains(dependency))
+            });
+            match next {
+                Some(index) => order.push(index),
+                None => {
+                    let cyclic: Vec<&str> = (0..passes.len())
+                        .filter(|index| !order.contains(index))
+                        .map(|index| passes[index].name.as_str())
+                        .collect();
+                    return Err(anyhow::Error::msg(format!(
+                        "Render passes {:?} depend on each other's outputs",
+                        cyclic
+                    )));
+                }
+            }
+        }
+        log::debug!(
+            "Scheduled render passes {:?}",
+            order
+                .iter()
+                .map(|index| passes[*index].name.as_str())
+                .collect::<Vec<_>>()
+        );
+        Ok(order)
+    }
+
+    fn create_depth_textures(&mut self) {
+        self.depth_textures.clear();
+        for pass in &self.passes {
+            let [width, height] = pass.size;
+            self.depth_textures.entry(pass.size).or_insert_with(|| {
+                DepthTexture::new(
+                    &self.graphics.device,
+                    wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    self.sample_count,
+                )
+            });
+        }
+    }
+
+    fn create_multisampled_targets(&mut self) {
+        self.multisampled_targets.clear();
+        if self.sample_count == 1 {
+            return;
+        }
+        let output_size = Self::output_size(&self.graphics);
+        for output in self
+            .passes
+            .iter()
+            .flat_map(|pass| pass.descriptor.outputs.iter())
+        {
+            let (format, size) = match self.targets.get(output) {
+                Some(target) => (target.format, target.size),
+                None => (self.graphics.get_internal_format(), output_size),
+            };
+            let multisampled_target =
+                MultisampledTarget::new(&self.graphics, format, size, self.sample_count);
+            self.multisampled_targets
+                .entry(output.clone())
+                .or_insert(multisampled_target);
+        }
+    }
+
+    fn output_size(graphics: &GraphicsDevice) -> [u32; 2] {
+        let size = graphics.create_render_extent3d(1);
+        [size.width, size.height]
+    }
+}
+
+struct GraphTarget {
+    descriptor: RenderTargetDescriptor,
+    format: wgpu::TextureFormat,
+    size: [u32; 2],
+    _texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+}
+
+impl GraphTarget {
+    fn new(
+        graphics: &GraphicsDevice,
+        descriptor: RenderTargetDescriptor,
+        output_size: [u32; 2],
+    ) -> Self {
+        log::trace!("Creating render target {}", descriptor.name);
+        let size = descriptor.resolve_size(output_size);
+        let format = match descriptor.format {
+            RenderTargetFormat::Output => graphics.get_internal_format(),
+            RenderTargetFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+            RenderTargetFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+            RenderTargetFormat::Rgba32Float => wgpu::TextureFormat::Rgba32Float,
+            RenderTargetFormat::R32Float => wgpu::TextureFormat::R32Float,
+        };
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(descriptor.name.as_str()),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = graphics.device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            ..Default::default()
+        });
+        Self {
+            descriptor,
+            format,
+            size,
+            _texture: texture,
+            texture_view,
+            sampler,
+        }
+    }
+}
+
+struct MultisampledTarget {
+    _texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+}
+
+impl MultisampledTarget {
+    fn new(
+        graphics: &GraphicsDevice,
+        format: wgpu::TextureFormat,
+        size: [u32; 2],
+        sample_count: u32,
+    ) -> Self {
+        log::trace!("Creating multisampled target with {} samples", sample_count);
+        let texture = graphics.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Multisampled Target"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            _texture: texture,
+            texture_view,
+        }
+    }
+}
+
+struct GraphPass {
+    descriptor: RenderPassDescriptor,
+    size: [u32; 2],
+    color_formats: Vec<wgpu::TextureFormat>,
+    meshes: Vec<Mesh>,
+    input_bind_group_layout: Option<wgpu::BindGroupLayout>,
+    input_bind_group: Option<wgpu::BindGroup>,
+}
+
+impl GraphPass {
+    fn new<ResourceCollection: CubensisResourceCollection>(
+        graphics: &Rc<GraphicsDevice>,
+        descriptor: RenderPassDescriptor,
+        scene: &Scene,
+        targets: &HashMap<String, GraphTarget>,
+        resource_collection: &ResourceCollection,
+        shared_layout: &PassLayout,
+    ) -> anyhow::Result<Self> {
+        log::debug!("Creating render pass {}", descriptor.name);
+        Self::validate(&descriptor, targets)?;
+        let output_size = RenderGraph::output_size(graphics);
+        let size = Self::resolve_size(&descriptor, targets, output_size);
+        if descriptor.outputs.iter().any(|output| {
+            targets
+                .get(output)
+                .map_or(output_size, |target| target.size)
+                != size
+        }) {
+            return Err(anyhow::Error::msg(format!(
+                "Outputs of render pass {} differ in size",
+                descriptor.name
+            )));
+        }
+        let color_formats: Vec<wgpu::TextureFormat> = descriptor
+            .outputs
+            .iter()
+            .map(|output| {
+                targets
+                    .get(output)
+                    .map_or(graphics.get_internal_format(), |target| target.format)
+            })
+            .collect();
+        let input_bind_group_layout = if descriptor.inputs.is_empty() {
+            None
+        } else {
+            Some(Self::create_input_bind_group_layout(graphics, &descriptor))
+        };
+        let input_bind_group = input_bind_group_layout
+            .as_ref()
+            .map(|layout| Self::create_input_bind_group(graphics, layout, &descriptor, targets));
+        let pass_layout = PassLayout {
+            input_bind_group_layout: input_bind_group_layout.as_ref(),
+            color_formats: color_formats.as_slice(),
+            ..*shared_layout
+        };
+        let bind_group_layouts = resource_collection.get_bind_group_layouts();
+        let mut meshes = Vec::new();
+        for mesh_name in &descriptor.meshes {
+            let mesh_descriptor = scene
+                .meshes
+                .iter()
+                .find(|mesh| &mesh.name == mesh_name)
+                .ok_or_else(|| {
+                    anyhow::Error::msg(format!(
+                        "Render pass {} draws unknown mesh {}",
+                        descriptor.name, mesh_name
+                    ))
+                })?;
+            meshes.push(Mesh::new(
+                graphics.clone(),
+                mesh_descriptor.clone(),
+                bind_group_layouts.as_slice(),
+                &pass_layout,
+            )?);
+        }
+        Ok(Self {
+            descriptor,
+            size,
+            color_formats,
+            meshes,
+            input_bind_group_layout,
+            input_bind_group,
+        })
+    }
+
+    fn validate(
+        descriptor: &RenderPassDescriptor,
+        targets: &HashMap<String, GraphTarget>,
+    ) -> anyhow::Result<()> {
+        let error = |message: String| {
+            Err(anyhow::Error::msg(format!(
+                "Render pass {}: {}",
+                descriptor.name, message
+            )))
+        };
+        if descriptor.outputs.is_empty() {
+            return error("at least one output is required".to_string());
+        }
+        for (index, output) in descriptor.outputs.iter().enumerate() {
+            if output != OUTPUT_TARGET && !targets.contains_key(output) {
+                return error(format!("unknown output {}", output));
+            }
+            if descriptor.outputs[..index].contains(output) {
+                return error(format!("output {} is written twice", output));
+            }
+        }
+        for input in &descriptor.inputs {
+            if input == OUTPUT_TARGET {
+                return error(format!(
+                    "{} can't be sampled, use the render history instead",
+                    OUTPUT_TARGET
+                ));
+            }
+            if !targets.contains_key(input) {
+                return error(format!("unknown input {}", input));
+            }
+            if descriptor.outputs.contains(input) {
+                return error(format!("{} is both read and written", input));
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_size(
+        descriptor: &RenderPassDescriptor,
+        targets: &HashMap<String, GraphTarget>,
+        output_size: [u32; 2],
+    ) -> [u32; 2] {
+        descriptor
+            .outputs
+            .first()
+            .and_then(|output| targets.get(output))
+            .map_or(output_size, |target| target.size)
+    }
+
+    ///Each input is bound as a texture at binding `2 * i` and its sampler at `2 * i + 1`
+    fn create_input_bind_group_layout(
+        graphics: &GraphicsDevice,
+        descriptor: &RenderPassDescriptor,
+    ) -> wgpu::BindGroupLayout {
+        let entries: Vec<wgpu::BindGroupLayoutEntry> = (0..descriptor.inputs.len() as u32)
+            .flat_map(|index| {
+                [
+                    wgpu::BindGroupLayoutEntry {
+                        binding: index * 2,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: index * 2 + 1,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Sampler {
+                            comparison: false,
+                            filtering: false,
+                        },
+                        count: None,
+                    },
+                ]
+            })
+            .collect();
+        graphics
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Render pass input bind group layout"),
+                entries: entries.as_slice(),
+            })
+    }
+
+    fn create_input_bind_group(
+        graphics: &GraphicsDevice,
+        layout: &wgpu::BindGroupLayout,
+        descriptor: &RenderPassDescriptor,
+        targets: &HashMap<String, GraphTarget>,
+    ) -> wgpu::BindGroup {
+        let entries: Vec<wgpu::BindGroupEntry> = descriptor
+            .inputs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, input)| {
+                let target = &targets[input];
+                [
+                    wgpu::BindGroupEntry {
+                        binding: index as u32 * 2,
+                        resource: wgpu::BindingResource::TextureView(&target.texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: index as u32 * 2 + 1,
+                        resource: wgpu::BindingResource::Sampler(&target.sampler),
+                    },
+                ]
+            })
+            .collect();
+        graphics
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Render pass input bind group"),
+                layout,
+                entries: entries.as_slice(),
+            })
+    }
+}
+
+trait IntoOperations {
+    fn color_operations(&self, is_first_draw: bool) -> wgpu::Operations<wgpu::Color>;
+    fn depth_operations(&self, is_first_draw: bool) -> wgpu::Operations<f32>;
+}
+
+impl IntoOperations for PassOperations {
+    fn color_operations(&self, is_first_draw: bool) -> wgpu::Operations<wgpu::Color> {
+        let [r, g, b, a] = self.clear_color;
+        let load = match Self::resolve_load(self.color_load, is_first_draw) {
+            LoadOperation::Clear => wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }),
+            LoadOperation::Load => wgpu::LoadOp::Load,
+        };
+        wgpu::Operations {
+            load,
+            store: self.color_store,
+        }
+    }
+
+    fn depth_operations(&self, is_first_draw: bool) -> wgpu::Operations<f32> {
+        let load = match Self::resolve_load(self.depth_load, is_first_draw) {
+            LoadOperation::Clear => wgpu::LoadOp::Clear(self.clear_depth),
+            LoadOperation::Load => wgpu::LoadOp::Load,
+        };
+        wgpu::Operations {
+            load,
+            store: self.depth_store,
+        }
+    }
+}
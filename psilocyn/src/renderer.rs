@@ -1,13 +1,14 @@
+use crate::capture::FrameCapture;
 use crate::device::GraphicsDevice;
 use crate::gui::{CubensisGuiApp, GuiHost};
-use crate::mesh::{CubensisMeshRenderPass, Mesh};
 use crate::presentation::presenter::CubensisPresenter;
 use crate::presentation::PresentationPass;
-use crate::resources::{CubensisMeshSpawner, CubensisResourceCollection};
+use crate::render_graph::RenderGraph;
+use crate::resources::{CubensisRenderGraphSpawner, CubensisResourceCollection};
 use crate::window::CubensisWindowBuilder;
 use hyphae::configuration::library::Library;
 use hyphae::configuration::Configuration;
-use hyphae::events::CubensisEvent;
+use hyphae::events::{CaptureOptions, CubensisEvent};
 use hyphae::plugins::CubensisPluginCollection;
 use hyphae::scene::Scene;
 use std::rc::Rc;
@@ -19,14 +20,14 @@ pub struct Renderer<
     ResourceCollection: 'static + CubensisResourceCollection,
     Gui: 'static + CubensisGuiApp<ResourceCollection>,
     Plugins: 'static + CubensisPluginCollection,
-    const HISTORY_DEPTH: usize,
 > {
     window: Rc<winit::window::Window>,
     graphics: Rc<GraphicsDevice>,
     resource_collection: ResourceCollection,
     gui_host: GuiHost,
-    presentation_pass: PresentationPass<HISTORY_DEPTH>,
-    meshes: Vec<Mesh>,
+    presentation_pass: PresentationPass,
+    capture: FrameCapture,
+    render_graph: RenderGraph,
     gui: Gui,
     _scene: Scene,
     _start_time: std::time::Instant,
@@ -39,52 +40,59 @@ impl<
         ResourceCollection: 'static + CubensisResourceCollection,
         Gui: 'static + CubensisGuiApp<ResourceCollection>,
         Plugins: 'static + CubensisPluginCollection,
-        const HISTORY_DEPTH: usize,
-    > Renderer<ResourceCollection, Gui, Plugins, HISTORY_DEPTH>
+    > Renderer<ResourceCollection, Gui, Plugins>
 {
     fn new(
         window: Rc<winit::window::Window>,
         event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>,
         configuration: Configuration,
-    ) -> Self {
+    ) -> anyhow::Result<Self> {
         log::debug!("Creating new renderer");
         let graphics = Rc::new(GraphicsDevice::new(
             configuration.clone(),
             &window,
             event_proxy.clone(),
         ));
-        let resource_collection = ResourceCollection::new(graphics.clone(), configuration.clone());
-        let gui_host = GuiHost::new(graphics.clone(), window.clone());
-        let presentation_pass = PresentationPass::new(graphics.clone());
         let library = configuration.library.build_library();
         let scene = library.current_scene();
+        graphics.set_scene_render_resolution(scene.render_resolution);
+        let resource_collection = ResourceCollection::new(graphics.clone(), configuration.clone())?;
+        let gui_host = GuiHost::new(graphics.clone(), window.clone());
+        let capture = FrameCapture::new(graphics.clone());
+        let presentation_pass =
+            PresentationPass::new(graphics.clone(), scene.history_depth, scene.expose_depth);
         let history_bind_group_layout = presentation_pass.get_bind_group_layout();
-        let meshes = scene.create_meshes(
+        let render_graph = scene.create_render_graph(
             graphics.clone(),
             &resource_collection,
             history_bind_group_layout,
-        );
-        let gui = Gui::new();
+        )?;
+        let diagnostics = render_graph.shader_diagnostics();
+        if !diagnostics.is_empty() {
+            event_proxy.send_event(CubensisEvent::ShaderDiagnostics(diagnostics))?;
+        }
+        let gui = Gui::new(event_proxy.clone());
         let start_time = std::time::Instant::now();
         let last_frame_time = start_time.clone();
         let plugins = Plugins::new(event_proxy.clone(), configuration.clone());
-        Self {
+        Ok(Self {
             window,
             graphics,
             resource_collection,
             gui_host,
             presentation_pass,
-            meshes,
+            capture,
+            render_graph,
             gui,
             _scene: scene.clone(),
             _start_time: start_time,
             last_frame_time,
             plugins,
             library,
-        }
+        })
     }
 
-    pub fn run(configuration: Configuration) -> ! {
+    pub fn run(configuration: Configuration) -> anyhow::Result<()> {
         log::debug!("Running renderer");
         let event_loop: winit::event_loop::EventLoop<CubensisEvent> =
             winit::event_loop::EventLoop::with_user_event();
@@ -93,7 +101,7 @@ impl<
         log::debug!("Creating event proxy");
         let proxy = event_loop.create_proxy();
         log::debug!("Initializing renderer");
-        let mut renderer = Self::new(window.clone(), proxy.clone(), configuration.clone());
+        let mut renderer = Self::new(window.clone(), proxy.clone(), configuration.clone())?;
         log::debug!("Beginning plugins");
         renderer.plugins.start_all();
         log::debug!("Entering event loop");
@@ -111,12 +119,35 @@ impl<
         self.plugins.handle_event(event);
         log::debug!("Handling application event");
         match event {
-            CubensisEvent::FileEdit(path) => self._scene.try_hot_reload(
-                path,
-                &mut self.meshes,
-                &self.resource_collection,
-                self.presentation_pass.get_bind_group_layout(),
-            ),
+            CubensisEvent::FileEdit(path) => {
+                let previous_diagnostics = self.render_graph.shader_diagnostics();
+                let result = self.render_graph.try_hot_reload(
+                    path,
+                    &self.resource_collection,
+                    self.presentation_pass.get_bind_group_layout(),
+                );
+                let diagnostics = self.render_graph.shader_diagnostics();
+                if diagnostics != previous_diagnostics {
+                    self.graphics
+                        .get_event_proxy()
+                        .send_event(CubensisEvent::ShaderDiagnostics(diagnostics))?;
+                }
+                result
+            }
+            CubensisEvent::Screenshot(options) => {
+                self.capture.request_screenshot(*options, &self._scene);
+                Ok(())
+            }
+            CubensisEvent::ToggleRecording(options) => {
+                let result = self.capture.toggle_recording(*options, &self._scene);
+                self.graphics
+                    .get_event_proxy()
+                    .send_event(CubensisEvent::RecordingChanged(self.capture.is_recording()))?;
+                result
+            }
+            CubensisEvent::SetMeshTransform { mesh, transform } => {
+                self.render_graph.set_mesh_transform(mesh, *transform)
+            }
             _ => Ok(()),
         }
     }
@@ -182,6 +213,34 @@ impl<
             WindowEvent::HoveredFileCancelled => {}
             WindowEvent::ReceivedCharacter(_) => {}
             WindowEvent::Focused(_) => {}
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state: winit::event::ElementState::Pressed,
+                        virtual_keycode: Some(winit::event::VirtualKeyCode::F12),
+                        ..
+                    },
+                ..
+            } => {
+                log::debug!("Screenshot hotkey pressed");
+                self.graphics
+                    .get_event_proxy()
+                    .send_event(CubensisEvent::Screenshot(CaptureOptions::default()))?
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state: winit::event::ElementState::Pressed,
+                        virtual_keycode: Some(winit::event::VirtualKeyCode::F10),
+                        ..
+                    },
+                ..
+            } => {
+                log::debug!("Recording hotkey pressed");
+                self.graphics
+                    .get_event_proxy()
+                    .send_event(CubensisEvent::ToggleRecording(CaptureOptions::default()))?
+            }
             WindowEvent::KeyboardInput { .. } => {}
             WindowEvent::ModifiersChanged(_) => {}
             WindowEvent::CursorMoved { .. } => {}
@@ -202,13 +261,33 @@ impl<
     }
 
     fn _recreate_scene(&mut self, scene: Scene) -> anyhow::Result<()> {
-        log::debug!("Recreating meshes");
+        log::debug!("Recreating render graph");
+        if self
+            .graphics
+            .set_scene_render_resolution(scene.render_resolution)
+        {
+            self.resource_collection.resize();
+            self.presentation_pass.resize();
+        }
+        if scene.history_depth != self.presentation_pass.get_history_depth()
+            || scene.expose_depth != self.presentation_pass.get_depth_history().is_some()
+        {
+            self.presentation_pass
+                .set_history(scene.history_depth, scene.expose_depth);
+        }
         let history_bind_group_layout = self.presentation_pass.get_bind_group_layout();
-        self.meshes = scene.create_meshes(
+        let previous_diagnostics = self.render_graph.shader_diagnostics();
+        self.render_graph = scene.create_render_graph(
             self.graphics.clone(),
             &self.resource_collection,
             history_bind_group_layout,
-        );
+        )?;
+        let diagnostics = self.render_graph.shader_diagnostics();
+        if diagnostics != previous_diagnostics {
+            self.graphics
+                .get_event_proxy()
+                .send_event(CubensisEvent::ShaderDiagnostics(diagnostics))?;
+        }
         self._scene = scene;
         Ok(())
     }
@@ -219,6 +298,7 @@ impl<
         let time_delta = current_frame_time - self.last_frame_time;
         self.last_frame_time = current_frame_time;
         self.resource_collection.update(time_delta);
+        self.render_graph.update(time_delta);
         self.gui_host.update(time_delta);
         self.gui.update(time_delta);
         Ok(())
@@ -226,9 +306,9 @@ impl<
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         log::trace!("Rendering");
+        self.capture.poll();
         self.presentation_pass.start_frame();
         let view = self.presentation_pass.create_presentation_view();
-        let depth_buffer = self.presentation_pass.get_depth_texture_view();
         let history_bind_group = self.presentation_pass.get_current_bind_group();
         let mut encoder =
             self.graphics
@@ -238,42 +318,27 @@ impl<
                 });
         let bind_groups = self.resource_collection.get_bind_groups();
 
-        for i in 0..self.meshes.len() {
-            let mesh = &self.meshes[i];
-            let renderpass_name = format!("Render Pass {}", i);
-
-            let mut renderpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some(renderpass_name.as_str()),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.01,
-                            g: 0.01,
-                            b: 0.01,
-                            a: 1.0,
-                        }),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: depth_buffer,
-                    depth_ops: Some(wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(1.0),
-                        store: true,
-                    }),
-                    stencil_ops: None,
-                }),
-            });
-            renderpass.draw_mesh_indexed(mesh, bind_groups.as_slice(), history_bind_group);
-        }
+        self.render_graph.encode(
+            &mut encoder,
+            bind_groups.as_slice(),
+            history_bind_group,
+            view,
+            self.presentation_pass.get_depth_history(),
+        );
+        self.presentation_pass.store_history(&mut encoder);
+        self.render_graph.encode_post_processing(
+            &mut encoder,
+            bind_groups.as_slice(),
+            self.presentation_pass.get_output_texture(),
+            self.presentation_pass.create_presentation_view(),
+        );
         encoder.present(
             &mut self.presentation_pass,
             &mut self.gui_host,
             &mut self.gui,
             &self.library,
             &self.resource_collection,
+            &mut self.capture,
         )?;
         Ok(())
     }
@@ -283,6 +348,7 @@ impl<
         if self.graphics.resize(size) {
             self.resource_collection.resize();
             self.presentation_pass.resize();
+            self.render_graph.resize();
         }
     }
 
@@ -329,8 +395,7 @@ impl<
         ResourceCollection: 'static + CubensisResourceCollection,
         Gui: 'static + CubensisGuiApp<ResourceCollection>,
         Plugins: 'static + CubensisPluginCollection,
-        const HISTORY_DEPTH: usize,
-    > Drop for Renderer<ResourceCollection, Gui, Plugins, HISTORY_DEPTH>
+    > Drop for Renderer<ResourceCollection, Gui, Plugins>
 {
     fn drop(&mut self) {
         self.plugins.shutdown();
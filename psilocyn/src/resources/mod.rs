@@ -1,13 +1,14 @@
 use crate::device::GraphicsDevice;
-use crate::mesh::Mesh;
-use hyphae::configuration::library::LibraryConfiguration;
+use crate::render_graph::RenderGraph;
 use hyphae::configuration::Configuration;
 use hyphae::events::CubensisEvent;
 use hyphae::scene::Scene;
-use std::path::PathBuf;
 use std::rc::Rc;
+use view::ViewRegion;
 use wgpu::BindGroupLayout;
 
+pub mod view;
+
 pub trait CubensisResource {
     ///returns true if bind group should be recreated
     fn update(&mut self, time_delta: std::time::Duration) -> bool;
@@ -21,15 +22,26 @@ pub trait CubensisResource {
         self.binding_offset() + Self::binding_size()
     }
     fn binding_size() -> u32;
+    ///WGSL declarations of the bindings, for the generated shader prelude
+    fn wgsl_declarations(binding_group: u32, binding_offset: u32) -> String;
+    ///GLSL declarations of the same bindings, under the same names as in WGSL
+    fn glsl_declarations(binding_group: u32, binding_offset: u32) -> String;
+    ///Restricts rendering to part of a larger image; affected uniforms are written immediately
+    fn set_view_region(&mut self, _region: &ViewRegion) {}
 }
 
-pub trait CubensisResourceCollection {
-    fn new(graphics: Rc<GraphicsDevice>, configuration: Configuration) -> Self;
+pub trait CubensisResourceCollection: Sized {
+    fn new(graphics: Rc<GraphicsDevice>, configuration: Configuration) -> anyhow::Result<Self>;
     fn update(&mut self, time_delta: std::time::Duration);
     fn resize(&mut self);
     fn get_bind_group_layouts(&self) -> Vec<&wgpu::BindGroupLayout>;
     fn get_bind_groups(&self) -> Vec<&wgpu::BindGroup>;
     fn handle_event(&mut self, event: &winit::event::Event<'_, CubensisEvent>);
+    fn set_view_region(&mut self, region: &ViewRegion);
+    ///WGSL declarations of every resource binding, included by shaders with `#include <cubensis>`
+    fn wgsl_prelude() -> String;
+    ///GLSL counterpart of the WGSL prelude, included by GLSL shaders with `#include <cubensis>`
+    fn glsl_prelude() -> String;
 }
 
 pub trait ResourceCollectionBinder<'a, ResourceCollection: CubensisResourceCollection> {
@@ -48,89 +60,29 @@ impl<'a, ResourceCollection: CubensisResourceCollection>
     }
 }
 
-pub trait CubensisMeshSpawner<ResourceCollection: CubensisResourceCollection> {
-    fn create_meshes(
+pub trait CubensisRenderGraphSpawner<ResourceCollection: CubensisResourceCollection> {
+    fn create_render_graph(
         &self,
         graphics: Rc<GraphicsDevice>,
         resource_collection: &ResourceCollection,
         history_bind_group_layout: &BindGroupLayout,
-    ) -> Vec<Mesh>;
-    fn try_hot_reload(
-        &self,
-        path: &PathBuf,
-        meshes: &mut Vec<Mesh>,
-        resource_collection: &ResourceCollection,
-        history_bind_group_layout: &BindGroupLayout,
-    ) -> anyhow::Result<()>;
+    ) -> anyhow::Result<RenderGraph>;
 }
 
-impl<ResourceCollection: CubensisResourceCollection> CubensisMeshSpawner<ResourceCollection>
+impl<ResourceCollection: CubensisResourceCollection> CubensisRenderGraphSpawner<ResourceCollection>
     for Scene
 {
-    fn create_meshes(
+    fn create_render_graph(
         &self,
         graphics: Rc<GraphicsDevice>,
         resource_collection: &ResourceCollection,
         history_bind_group_layout: &BindGroupLayout,
-    ) -> Vec<Mesh> {
-        log::debug!("Creating meshes");
-        self.meshes
-            .iter()
-            .map(|m| {
-                Mesh::new(
-                    graphics.clone(),
-                    m.clone(),
-                    resource_collection.get_bind_group_layouts().as_slice(),
-                    history_bind_group_layout,
-                )
-            })
-            .collect()
-    }
-
-    fn try_hot_reload(
-        &self,
-        path: &PathBuf,
-        meshes: &mut Vec<Mesh>,
-        resource_collection: &ResourceCollection,
-        history_bind_group_layout: &BindGroupLayout,
-    ) -> anyhow::Result<()> {
-        log::debug!("Attempting hot reload of shader at path {:?}", path);
-        for mesh in meshes.iter_mut() {
-            let shader_to_update = mesh.mesh_descriptor.render_shaders.iter().find(|s| {
-                let mut ref_path = std::path::Path::new(&s.path);
-                let canonical_path = LibraryConfiguration::scene_library_path()
-                    .join(ref_path)
-                    .canonicalize()
-                    .unwrap();
-                if ref_path.is_relative() {
-                    ref_path = &canonical_path;
-                }
-                let res = path.canonicalize().unwrap() == ref_path;
-                log::debug!("Checking ({}) for path {:?}", &res, &ref_path);
-                res
-            });
-            if let Some(render_shader) = shader_to_update {
-                log::debug!("Rebuilding shader named {}", render_shader.name);
-                let render_shader = render_shader.clone();
-                mesh.rebuild(
-                    render_shader,
-                    resource_collection,
-                    history_bind_group_layout,
-                )?;
-            }
-
-            return Ok(());
-        }
-        Err(anyhow::Error::msg(
-            "Updated path doesn't correspond to any loaded meshes/shaders",
-        ))
+    ) -> anyhow::Result<RenderGraph> {
+        RenderGraph::new(
+            graphics,
+            self,
+            resource_collection,
+            history_bind_group_layout,
+        )
     }
-    // let extension = path.extension().map(|e| e.to_str()).unwrap_or("".into());
-    // todo: this block is potentially useful for rebuilding the scene, but not needed for shader hot-reload
-    // if let Some("cubensis-scene") = extension {
-    //     let mut new_meshes =
-    //         self.create_meshes(graphics, resource_collection, history_bind_group_layout);
-    //     meshes.clear();
-    //     meshes.append(&mut new_meshes);
-    // }
 }
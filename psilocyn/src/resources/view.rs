@@ -0,0 +1,45 @@
+///The part of the final image a frame covers, in pixels.
+///Tiled rendering draws several regions of one image that is larger than any render target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewRegion {
+    pub full_size: winit::dpi::PhysicalSize<u32>,
+    pub offset: winit::dpi::PhysicalPosition<u32>,
+    pub size: winit::dpi::PhysicalSize<u32>,
+}
+
+impl ViewRegion {
+    ///A region covering the whole image
+    pub fn full(size: winit::dpi::PhysicalSize<u32>) -> Self {
+        Self {
+            full_size: size,
+            offset: winit::dpi::PhysicalPosition::new(0, 0),
+            size,
+        }
+    }
+
+    ///Column-major matrix mapping clip space of the full image onto clip space of this region
+    pub fn clip_transform(&self) -> [[f32; 4]; 4] {
+        let full_width = self.full_size.width as f32;
+        let full_height = self.full_size.height as f32;
+        let width = self.size.width as f32;
+        let height = self.size.height as f32;
+        let left = 2.0 * self.offset.x as f32 / full_width - 1.0;
+        let right = 2.0 * (self.offset.x as f32 + width) / full_width - 1.0;
+        //pixel rows grow downwards while clip space y grows upwards
+        let top = 1.0 - 2.0 * self.offset.y as f32 / full_height;
+        let bottom = 1.0 - 2.0 * (self.offset.y as f32 + height) / full_height;
+        let scale_x = full_width / width;
+        let scale_y = full_height / height;
+        [
+            [scale_x, 0.0, 0.0, 0.0],
+            [0.0, scale_y, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [
+                -(left + right) * 0.5 * scale_x,
+                -(top + bottom) * 0.5 * scale_y,
+                0.0,
+                1.0,
+            ],
+        ]
+    }
+}
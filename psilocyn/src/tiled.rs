@@ -0,0 +1,98 @@
+use crate::headless::HeadlessRenderer;
+use crate::resources::view::ViewRegion;
+use crate::resources::CubensisResourceCollection;
+use hyphae::configuration::Configuration;
+use image::GenericImage;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub struct TiledSettings {
+    ///Size of the stitched image, which may exceed the device's `max_texture_dimension`
+    pub size: winit::dpi::PhysicalSize<u32>,
+    pub tile_size: u32,
+    ///Instant all tiles are rendered at
+    pub time: Duration,
+    ///Frames drawn per tile before reading it back, so history textures hold the same region
+    pub settle_frames: u32,
+    pub output: PathBuf,
+}
+
+///Renders a single paused frame in tiles and stitches them into one large image
+pub struct TiledExporter<ResourceCollection: CubensisResourceCollection> {
+    renderer: HeadlessRenderer<ResourceCollection>,
+    settings: TiledSettings,
+}
+
+impl<ResourceCollection: CubensisResourceCollection> TiledExporter<ResourceCollection> {
+    pub fn new(configuration: Configuration, settings: TiledSettings) -> anyhow::Result<Self> {
+        log::debug!("Creating tiled exporter");
+        if settings.tile_size == 0 {
+            return Err(anyhow::Error::msg("Tile size must not be zero"));
+        }
+        if settings.size.width == 0 || settings.size.height == 0 {
+            return Err(anyhow::Error::msg("Image size must not be zero"));
+        }
+        let tile_size = winit::dpi::PhysicalSize::new(settings.tile_size, settings.tile_size);
+        //the renderer rejects tiles larger than its device's texture limit
+        let renderer = HeadlessRenderer::new(configuration, tile_size)?;
+        Ok(Self { renderer, settings })
+    }
+
+    pub fn run(&mut self) -> anyhow::Result<()> {
+        let columns = Self::tile_count(self.settings.size.width, self.settings.tile_size);
+        let rows = Self::tile_count(self.settings.size.height, self.settings.tile_size);
+        log::info!(
+            "Rendering {}x{} image in {} tiles",
+            self.settings.size.width,
+            self.settings.size.height,
+            columns * rows
+        );
+        //time and audio are advanced exactly once, then every tile draws the same instant
+        self.renderer.advance(self.settings.time);
+        let mut image = image::RgbaImage::new(self.settings.size.width, self.settings.size.height);
+        for row in 0..rows {
+            for column in 0..columns {
+                let offset = winit::dpi::PhysicalPosition::new(
+                    column * self.settings.tile_size,
+                    row * self.settings.tile_size,
+                );
+                //edge tiles keep the full tile size and extend past the image, only the inside is kept
+                let region = ViewRegion {
+                    full_size: self.settings.size,
+                    offset,
+                    size: winit::dpi::PhysicalSize::new(
+                        self.settings.tile_size,
+                        self.settings.tile_size,
+                    ),
+                };
+                let tile = self.render_tile(&region)?;
+                let visible_width = (self.settings.size.width - offset.x).min(tile.width());
+                let visible_height = (self.settings.size.height - offset.y).min(tile.height());
+                let visible_tile =
+                    image::imageops::crop_imm(&tile, 0, 0, visible_width, visible_height);
+                image.copy_from(&visible_tile, offset.x, offset.y)?;
+                log::debug!(
+                    "Rendered tile {}/{}",
+                    row * columns + column + 1,
+                    columns * rows
+                );
+            }
+        }
+        image.save(&self.settings.output)?;
+        log::info!("Saved tiled image to {:?}", self.settings.output);
+        Ok(())
+    }
+
+    fn render_tile(&mut self, region: &ViewRegion) -> anyhow::Result<image::RgbaImage> {
+        log::trace!("Rendering tile {:?}", region);
+        self.renderer.set_view_region(region);
+        for _ in 0..self.settings.settle_frames.max(1) {
+            self.renderer.draw_frame();
+        }
+        self.renderer.read_frame()
+    }
+
+    fn tile_count(length: u32, tile_size: u32) -> u32 {
+        length.div_ceil(tile_size)
+    }
+}
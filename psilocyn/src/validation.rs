@@ -10,11 +10,17 @@ impl CubensisValidatedShader for String {
         log::debug!("Validating shader");
         log::trace!("Validating shader source: {}", &self);
         let module = naga::front::wgsl::parse_str(&self)?;
-        let mut validator = naga::valid::Validator::new(
-            naga::valid::ValidationFlags::all(),
-            naga::valid::Capabilities::FLOAT64,
-        );
-        let _module_info = validator.validate(&module)?;
+        validate_module(&module)?;
         Ok(self)
     }
 }
+
+pub(crate) fn validate_module(
+    module: &naga::Module,
+) -> Result<naga::valid::ModuleInfo, naga::valid::ValidationError> {
+    let mut validator = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::FLOAT64,
+    );
+    validator.validate(module)
+}
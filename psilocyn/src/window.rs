@@ -1,5 +1,6 @@
 use hyphae::events::CubensisEvent;
 use image::ImageFormat;
+#[cfg(windows)]
 use winit::platform::windows::WindowBuilderExtWindows;
 
 pub(crate) trait CubensisWindowBuilder {
@@ -0,0 +1,95 @@
+// Generates a 64x64 vertex grid displaced by the audio spectrum.
+// Dispatch with "workgroups": [64, 1, 1], "max_vertices": 4096 and "max_indices": 23814.
+struct Vertex {
+    position: array<f32, 3>;
+    uv: array<f32, 2>;
+    normal: array<f32, 3>;
+    color: array<f32, 4>;
+    tangent: array<f32, 4>;
+};
+
+[[block]]
+struct Vertices {
+    data: array<Vertex>;
+};
+
+[[block]]
+struct Indices {
+    data: array<u32>;
+};
+
+[[block]]
+struct DrawIndexedIndirect {
+    index_count: u32;
+    instance_count: u32;
+    base_index: u32;
+    vertex_offset: i32;
+    base_instance: u32;
+};
+
+[[block]]
+struct TimeInfo {
+    frame_index: u32;
+    time_seconds: f32;
+    frame_time_seconds: f32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> time_info: TimeInfo;
+[[group(0), binding(3)]]
+var spectrum_texture: texture_1d<f32>;
+// Geometry, bound after the resource groups
+[[group(1), binding(0)]]
+var<storage, read_write> vertices: Vertices;
+[[group(1), binding(1)]]
+var<storage, read_write> indices: Indices;
+[[group(1), binding(2)]]
+var<storage, read_write> draw_arguments: DrawIndexedIndirect;
+
+let GRID_SIZE: u32 = 64u;
+
+[[stage(compute), workgroup_size(64)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    let vertex_index = id.x;
+    if (vertex_index >= GRID_SIZE * GRID_SIZE) {
+        return;
+    }
+    let column = vertex_index % GRID_SIZE;
+    let row = vertex_index / GRID_SIZE;
+    let uv = vec2<f32>(f32(column), f32(row)) / f32(GRID_SIZE - 1u);
+    let spectrum_length = textureDimensions(spectrum_texture);
+    let spectrum_value = textureLoad(spectrum_texture, i32(uv.x * f32(spectrum_length - 1)), 0).r;
+    let height = spectrum_value * 0.5 * (1.0 + sin(uv.y * 6.28 + time_info.time_seconds));
+
+    var vertex: Vertex;
+    vertex.position[0] = uv.x * 2.0 - 1.0;
+    vertex.position[1] = uv.y * 2.0 - 1.0;
+    vertex.position[2] = 0.5 - height * 0.25;
+    vertex.uv[0] = uv.x;
+    vertex.uv[1] = uv.y;
+    vertex.normal[0] = 0.0;
+    vertex.normal[1] = 0.0;
+    vertex.normal[2] = -1.0;
+    vertex.color[0] = 1.0;
+    vertex.color[1] = 1.0;
+    vertex.color[2] = 1.0;
+    vertex.color[3] = 1.0;
+    vertex.tangent[0] = 1.0;
+    vertex.tangent[1] = 0.0;
+    vertex.tangent[2] = 0.0;
+    vertex.tangent[3] = 1.0;
+    vertices.data[vertex_index] = vertex;
+
+    if (column < GRID_SIZE - 1u && row < GRID_SIZE - 1u) {
+        let first_index = (row * (GRID_SIZE - 1u) + column) * 6u;
+        indices.data[first_index] = vertex_index;
+        indices.data[first_index + 1u] = vertex_index + GRID_SIZE + 1u;
+        indices.data[first_index + 2u] = vertex_index + 1u;
+        indices.data[first_index + 3u] = vertex_index;
+        indices.data[first_index + 4u] = vertex_index + GRID_SIZE;
+        indices.data[first_index + 5u] = vertex_index + GRID_SIZE + 1u;
+    }
+    if (vertex_index == 0u) {
+        draw_arguments.index_count = (GRID_SIZE - 1u) * (GRID_SIZE - 1u) * 6u;
+    }
+}
@@ -0,0 +1,53 @@
+// Draws one spectrum bar per instance of a unit Cube.
+// Use with "instance_count": 512 and "instances": "Spectrum".
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] normal: vec3<f32>;
+    [[location(3)]] color: vec4<f32>;
+    [[location(4)]] tangent: vec4<f32>;
+};
+
+// Per instance attributes
+struct InstanceInput {
+    // xyz is the offset, w the scale
+    [[location(5)]] offset_scale: vec4<f32>;
+    [[location(6)]] color: vec4<f32>;
+    // Spectrum: x is the bin value, y the position of the bar from 0 to 1
+    [[location(7)]] data: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+    [[location(1)]] shade: f32;
+};
+
+let BAR_COUNT: f32 = 512.0;
+
+[[stage(vertex)]]
+fn main(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[builtin(instance_index)]] instance_index: u32,
+) -> VertexOutput {
+    let height = clamp(instance.data.x, 0.0, 1.0) * 1.5 + 0.01;
+    // unit cube from -0.5 to 0.5, standing on the bottom of the screen
+    let position = vec3<f32>(
+        model.position.x * 2.0 / BAR_COUNT,
+        (model.position.y + 0.5) * height - 0.9,
+        model.position.z * 0.1 + 0.5,
+    ) * instance.offset_scale.w + instance.offset_scale.xyz;
+    var out: VertexOutput;
+    out.position = vec4<f32>(position, 1.0);
+    // alternate the shade of neighbouring bars
+    let stripe = f32(instance_index % 2u) * 0.2 + 0.8;
+    out.color = instance.color * vec4<f32>(instance.data.y, 0.4 + height * 0.4, 1.0 - instance.data.y, 1.0);
+    out.shade = stripe * (0.6 + 0.4 * max(model.normal.z, 0.0));
+    return out;
+}
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return vec4<f32>(in.color.rgb * in.shade, in.color.a);
+}
@@ -0,0 +1,76 @@
+// Emits particles from the origin, faster on loud spectrum bands.
+// Particle shaders declare workgroup_size(64); the emit shader runs one invocation per spawned particle.
+struct Particle {
+    position: vec3<f32>;
+    size: f32;
+    velocity: vec3<f32>;
+    age: f32;
+    color: vec4<f32>;
+    lifetime: f32;
+    alive: u32;
+    seed: u32;
+    padding: u32;
+};
+
+[[block]]
+struct Particles {
+    data: array<Particle>;
+};
+
+[[block]]
+struct ParticleSystemInfo {
+    capacity: u32;
+    spawn_count: u32;
+    spawn_offset: u32;
+    time_delta: f32;
+    step_index: u32;
+};
+
+[[group(0), binding(3)]]
+var spectrum_texture: texture_1d<f32>;
+// Particle state, bound after the resource groups
+[[group(1), binding(0)]]
+var<storage, read_write> particles: Particles;
+[[group(1), binding(1)]]
+var<uniform> info: ParticleSystemInfo;
+
+fn hash(value: u32) -> u32 {
+    var x = value;
+    x = x ^ (x >> 16u);
+    x = x * 2146121005u;
+    x = x ^ (x >> 15u);
+    x = x * 2221713035u;
+    x = x ^ (x >> 16u);
+    return x;
+}
+
+fn random(seed: u32) -> f32 {
+    return f32(hash(seed)) / 4294967295.0;
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    if (id.x >= info.spawn_count) {
+        return;
+    }
+    let index = (info.spawn_offset + id.x) % info.capacity;
+    let seed = hash(info.step_index * 65536u + id.x);
+    let band = random(seed);
+    let spectrum_length = textureDimensions(spectrum_texture);
+    let energy = textureLoad(spectrum_texture, i32(band * f32(spectrum_length - 1)), 0).r;
+    let angle = random(seed + 1u) * 6.2831853;
+    let elevation = random(seed + 2u) * 2.0 - 1.0;
+    let radius = sqrt(1.0 - elevation * elevation);
+    let direction = vec3<f32>(cos(angle) * radius, elevation, sin(angle) * radius);
+
+    var particle: Particle;
+    particle.position = vec3<f32>(0.0, 0.0, 0.0);
+    particle.size = 0.005 + 0.015 * random(seed + 3u);
+    particle.velocity = direction * (0.1 + energy * 2.0);
+    particle.age = 0.0;
+    particle.color = vec4<f32>(band, 0.4 + energy, 1.0 - band, 1.0);
+    particle.lifetime = 1.0 + 2.0 * random(seed + 4u);
+    particle.alive = 1u;
+    particle.seed = seed;
+    particles.data[index] = particle;
+}
@@ -0,0 +1,60 @@
+// Draws particles as camera facing billboards; the particle state arrives as instance attributes 8 to 14.
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] uv: vec2<f32>;
+};
+
+struct ParticleInput {
+    [[location(8)]] position: vec3<f32>;
+    [[location(9)]] size: f32;
+    [[location(12)]] color: vec4<f32>;
+    [[location(14)]] alive: u32;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[block]]
+struct Camera{
+    translation: mat4x4<f32>;
+    center_translation: mat4x4<f32>;
+    rotation: vec4<f32>;
+    camera: mat4x4<f32>;
+    inv_camera: mat4x4<f32>;
+    zoom_speed: f32;
+    inv_screen: vec2<f32>;
+    perspective_projection: mat4x4<f32>;
+    projection: mat4x4<f32>;
+};
+
+[[group(0), binding(1)]]
+var<uniform> camera: Camera;
+
+[[stage(vertex)]]
+fn main(
+    model: VertexInput,
+    particle: ParticleInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.uv = model.uv;
+    out.color = particle.color;
+    if (particle.alive == 0u) {
+        // outside the clip volume
+        out.position = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+        return out;
+    }
+    let center = camera.perspective_projection * camera.camera * vec4<f32>(particle.position, 1.0);
+    let corner = model.position.xy * particle.size * vec2<f32>(camera.inv_screen.x / camera.inv_screen.y, 1.0);
+    out.position = center + vec4<f32>(corner * center.w, 0.0, 0.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let distance = length(in.uv * 2.0 - vec2<f32>(1.0, 1.0));
+    let falloff = clamp(1.0 - distance, 0.0, 1.0);
+    return vec4<f32>(in.color.rgb, in.color.a * falloff);
+}
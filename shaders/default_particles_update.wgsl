@@ -0,0 +1,52 @@
+// Advances every live particle under drag and gravity, fading it out over its lifetime.
+// The update shader runs one invocation per particle of the capacity.
+struct Particle {
+    position: vec3<f32>;
+    size: f32;
+    velocity: vec3<f32>;
+    age: f32;
+    color: vec4<f32>;
+    lifetime: f32;
+    alive: u32;
+    seed: u32;
+    padding: u32;
+};
+
+[[block]]
+struct Particles {
+    data: array<Particle>;
+};
+
+[[block]]
+struct ParticleSystemInfo {
+    capacity: u32;
+    spawn_count: u32;
+    spawn_offset: u32;
+    time_delta: f32;
+    step_index: u32;
+};
+
+[[group(1), binding(0)]]
+var<storage, read_write> particles: Particles;
+[[group(1), binding(1)]]
+var<uniform> info: ParticleSystemInfo;
+
+[[stage(compute), workgroup_size(64)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    if (id.x >= info.capacity) {
+        return;
+    }
+    var particle = particles.data[id.x];
+    if (particle.alive == 0u) {
+        return;
+    }
+    let time_delta = info.time_delta;
+    particle.age = particle.age + time_delta;
+    if (particle.age >= particle.lifetime) {
+        particle.alive = 0u;
+    }
+    particle.velocity = particle.velocity * (1.0 - 0.5 * time_delta) + vec3<f32>(0.0, -0.3, 0.0) * time_delta;
+    particle.position = particle.position + particle.velocity * time_delta;
+    particle.color.w = 1.0 - particle.age / particle.lifetime;
+    particles.data[id.x] = particle;
+}
@@ -1,6 +1,10 @@
 struct VertexInput {
     [[location(0)]] position: vec3<f32>;
     [[location(1)]] uv: vec2<f32>;
+    [[location(2)]] normal: vec3<f32>;
+    [[location(3)]] color: vec4<f32>;
+    // w is the bitangent handedness: bitangent = cross(normal, tangent.xyz) * tangent.w
+    [[location(4)]] tangent: vec4<f32>;
 };
 
 struct VertexOutput {
@@ -28,6 +32,20 @@ struct Camera{
     projection: mat4x4<f32>;
 };
 
+// Model matrix of the mesh and its inverse transpose for normals
+[[block]]
+struct MeshTransform {
+    model: mat4x4<f32>;
+    normal: mat4x4<f32>;
+};
+
+[[block]]
+struct ViewInfo {
+    resolution: vec2<f32>;
+    region_offset: vec2<f32>;
+    region_size: vec2<f32>;
+};
+
 [[group(0), binding(0)]]
 var<uniform> time_info: TimeInfo;
 [[group(0), binding(1)]]
@@ -38,18 +56,28 @@ var wave_texture: texture_1d<f32>;
 var spectrum_texture: texture_1d<f32>;
 [[group(0), binding(4)]]
 var audio_sampler : sampler;
-// Render History
+[[group(0), binding(11)]]
+var<uniform> view_info: ViewInfo;
+// Render History, the previous frame while the scene keeps a history_depth of 1
 [[group(1), binding(0)]]
 var history_texture: texture_2d<f32>;
 [[group(1), binding(1)]]
 var history_sampler : sampler;
+// Depth, only written when the scene sets expose_depth; read with textureLoad
+[[group(1), binding(3)]]
+var prior_depth_texture: texture_depth_2d;
+[[group(1), binding(4)]]
+var previous_depth_texture: texture_depth_2d;
+
+[[group(3), binding(0)]]
+var<uniform> mesh_transform: MeshTransform;
 
 [[stage(vertex)]]
 fn main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.position = vec4<f32>(model.position, 1.0);
+    out.position = mesh_transform.model * vec4<f32>(model.position, 1.0);
     out.uv = model.uv;
     return out;
 }
@@ -57,7 +85,7 @@ fn main(
 
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-    var prev_color = textureSample(history_texture, history_sampler, 1.0 - in.uv ) * sin(time_info.time_seconds);
+    var prev_color = textureSample(history_texture, history_sampler, 1.0 - in.uv) * sin(time_info.time_seconds);
     var spectrum_value = textureSample(spectrum_texture, audio_sampler, in.uv.x ).r;
     return vec4<f32>(spectrum_value * 2.0, in.uv.x, in.uv.y, 1.0) + vec4<f32>(prev_color.grb, 0.5);
 }
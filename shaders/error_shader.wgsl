@@ -0,0 +1,36 @@
+// Drawn in place of render shaders that fail to build, until hot-reload fixes them
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+};
+
+[[block]]
+struct MeshTransform {
+    model: mat4x4<f32>;
+    normal: mat4x4<f32>;
+};
+
+[[group(3), binding(0)]]
+var<uniform> mesh_transform: MeshTransform;
+
+[[stage(vertex)]]
+fn main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = mesh_transform.model * vec4<f32>(model.position, 1.0);
+    return out;
+}
+
+// Magenta checkerboard of 16 pixel squares
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let square = vec2<i32>(floor(in.position.xy / 16.0));
+    if ((square.x + square.y) % 2 == 0) {
+        return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
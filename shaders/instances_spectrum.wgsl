@@ -0,0 +1,53 @@
+// Built-in instance source "Spectrum": spreads the instances along X across clip space
+// and hands every instance one bin of the power spectrum.
+struct Instance {
+    offset: array<f32, 3>;
+    scale: f32;
+    color: array<f32, 4>;
+    data: array<f32, 4>;
+};
+
+[[block]]
+struct Instances {
+    data: array<Instance>;
+};
+
+[[block]]
+struct InstanceInfo {
+    count: u32;
+};
+
+[[group(0), binding(3)]]
+var spectrum_texture: texture_1d<f32>;
+// Instances, bound after the resource groups
+[[group(1), binding(0)]]
+var<storage, read_write> instances: Instances;
+[[group(1), binding(1)]]
+var<uniform> instance_info: InstanceInfo;
+
+[[stage(compute), workgroup_size(64)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    let instance_index = id.x;
+    if (instance_index >= instance_info.count) {
+        return;
+    }
+    let position = (f32(instance_index) + 0.5) / f32(instance_info.count);
+    let spectrum_length = textureDimensions(spectrum_texture);
+    let bin = min(i32(position * f32(spectrum_length)), spectrum_length - 1);
+    let value = textureLoad(spectrum_texture, bin, 0).r;
+
+    var instance: Instance;
+    instance.offset[0] = position * 2.0 - 1.0;
+    instance.offset[1] = 0.0;
+    instance.offset[2] = 0.0;
+    instance.scale = 1.0;
+    instance.color[0] = 1.0;
+    instance.color[1] = 1.0;
+    instance.color[2] = 1.0;
+    instance.color[3] = 1.0;
+    instance.data[0] = value;
+    instance.data[1] = position;
+    instance.data[2] = 0.0;
+    instance.data[3] = 0.0;
+    instances.data[instance_index] = instance;
+}
@@ -0,0 +1,19 @@
+// Bloom: x threshold, y intensity, z radius.
+// Stages: extract bright parts, blur horizontally, blur vertically, add onto the effect input.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    if (stage_info.stage == 0u) {
+        let color = sample_input(in.uv);
+        let brightness = max(color.r, max(color.g, color.b));
+        let contribution = max(brightness - in.parameters.x, 0.0) / max(brightness, 0.0001);
+        return vec4<f32>(color.rgb * contribution, 1.0);
+    }
+    if (stage_info.stage == 1u) {
+        return gaussian_blur(in.uv, vec2<f32>(1.0, 0.0), in.parameters.z);
+    }
+    if (stage_info.stage == 2u) {
+        return gaussian_blur(in.uv, vec2<f32>(0.0, 1.0), in.parameters.z);
+    }
+    let source = textureSampleLevel(source_texture, post_sampler, in.uv, 0.0);
+    return vec4<f32>(source.rgb + sample_input(in.uv).rgb * in.parameters.y, source.a);
+}
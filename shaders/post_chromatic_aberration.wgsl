@@ -0,0 +1,12 @@
+// Chromatic aberration: x strength.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let offset = (in.uv - vec2<f32>(0.5)) * 2.0 * in.parameters.x;
+    let color = sample_input(in.uv);
+    return vec4<f32>(
+        sample_input(in.uv + offset).r,
+        color.g,
+        sample_input(in.uv - offset).b,
+        color.a,
+    );
+}
@@ -0,0 +1,14 @@
+// Colour grading: x exposure, y contrast, z saturation, w hue shift in degrees.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let original = sample_input(in.uv);
+    var color = original.rgb * in.parameters.x;
+    color = (color - vec3<f32>(0.5)) * in.parameters.y + vec3<f32>(0.5);
+    let luma = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    color = mix(vec3<f32>(luma), color, vec3<f32>(in.parameters.z));
+    // Rodrigues rotation around the grey axis
+    let axis = vec3<f32>(0.57735027);
+    let angle = in.parameters.w * 0.01745329;
+    color = color * cos(angle) + cross(axis, color) * sin(angle) + axis * dot(axis, color) * (1.0 - cos(angle));
+    return vec4<f32>(max(color, vec3<f32>(0.0)), original.a);
+}
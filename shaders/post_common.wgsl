@@ -0,0 +1,128 @@
+// Shared by the built-in post effects, which append their fragment stage.
+// Parameters are modulated once per draw in the vertex stage and passed on flat.
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] uv: vec2<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(1)]] uv: vec2<f32>;
+    [[location(2), interpolate(flat)]] parameters: vec4<f32>;
+};
+
+[[block]]
+struct TimeInfo {
+    frame_index: u32;
+    time_seconds: f32;
+    frame_time_seconds: f32;
+};
+
+// Every parameter is (value, feature, amount, unused), see hyphae::scene::post::AudioFeature
+[[block]]
+struct PostStageInfo {
+    parameters: array<vec4<f32>, 4>;
+    stage: u32;
+    stage_count: u32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> time_info: TimeInfo;
+[[group(0), binding(2)]]
+var wave_texture: texture_1d<f32>;
+[[group(0), binding(3)]]
+var spectrum_texture: texture_1d<f32>;
+// Output of the previous stage, and the input of the effect
+[[group(1), binding(0)]]
+var input_texture: texture_2d<f32>;
+[[group(1), binding(1)]]
+var source_texture: texture_2d<f32>;
+[[group(1), binding(2)]]
+var post_sampler: sampler;
+[[group(1), binding(3)]]
+var<uniform> stage_info: PostStageInfo;
+
+let FEATURE_LEVEL: u32 = 1u;
+let FEATURE_BASS: u32 = 2u;
+let FEATURE_MID: u32 = 3u;
+let FEATURE_TREBLE: u32 = 4u;
+
+// The spectrum spans 10 Hz to 8 kHz linearly
+fn spectrum_mean(from_hz: f32, to_hz: f32) -> f32 {
+    let length = textureDimensions(spectrum_texture);
+    let first = clamp(i32((from_hz - 10.0) / 7990.0 * f32(length)), 0, length - 1);
+    let last = clamp(i32((to_hz - 10.0) / 7990.0 * f32(length)), first + 1, length);
+    var sum = 0.0;
+    for (var bin = first; bin < last; bin = bin + 1) {
+        sum = sum + textureLoad(spectrum_texture, bin, 0).r;
+    }
+    return sum / f32(last - first);
+}
+
+fn wave_level() -> f32 {
+    let length = textureDimensions(wave_texture);
+    var sum = 0.0;
+    for (var sample = 0; sample < length; sample = sample + 1) {
+        let value = textureLoad(wave_texture, sample, 0).r;
+        sum = sum + value * value;
+    }
+    return sqrt(sum / f32(max(length, 1)));
+}
+
+fn audio_feature(feature: u32) -> f32 {
+    if (feature == FEATURE_LEVEL) {
+        return wave_level();
+    }
+    if (feature == FEATURE_BASS) {
+        return spectrum_mean(10.0, 250.0);
+    }
+    if (feature == FEATURE_MID) {
+        return spectrum_mean(250.0, 2000.0);
+    }
+    if (feature == FEATURE_TREBLE) {
+        return spectrum_mean(2000.0, 8000.0);
+    }
+    return 0.0;
+}
+
+fn modulated_parameter(index: i32) -> f32 {
+    let parameter = stage_info.parameters[index];
+    return parameter.x + parameter.z * audio_feature(u32(parameter.y));
+}
+
+[[stage(vertex)]]
+fn main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(model.position, 1.0);
+    out.uv = model.uv;
+    out.parameters = vec4<f32>(
+        modulated_parameter(0),
+        modulated_parameter(1),
+        modulated_parameter(2),
+        modulated_parameter(3),
+    );
+    return out;
+}
+
+fn sample_input(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(input_texture, post_sampler, uv, 0.0);
+}
+
+fn input_texel_size() -> vec2<f32> {
+    return 1.0 / vec2<f32>(textureDimensions(input_texture));
+}
+
+// 9 tap gaussian along `direction`, with `radius` pixels between taps
+fn gaussian_blur(uv: vec2<f32>, direction: vec2<f32>, radius: f32) -> vec4<f32> {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let step = direction * input_texel_size() * radius;
+    var color = sample_input(uv) * weights[0];
+    for (var tap = 1; tap < 5; tap = tap + 1) {
+        let offset = step * f32(tap);
+        color = color + (sample_input(uv + offset) + sample_input(uv - offset)) * weights[tap];
+    }
+    return color;
+}
+
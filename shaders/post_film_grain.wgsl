@@ -0,0 +1,12 @@
+// Film grain: x intensity.
+fn hash(point: vec2<f32>) -> f32 {
+    return fract(sin(dot(point, vec2<f32>(12.9898, 78.233))) * 43758.5453);
+}
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let color = sample_input(in.uv);
+    let pixel = floor(in.uv * vec2<f32>(textureDimensions(input_texture)));
+    let noise = hash(pixel + vec2<f32>(fract(time_info.time_seconds) * 1000.0)) - 0.5;
+    return vec4<f32>(color.rgb + vec3<f32>(noise * in.parameters.x), color.a);
+}
@@ -0,0 +1,8 @@
+// Gaussian blur: x radius. Stages: horizontal, vertical.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    if (stage_info.stage == 0u) {
+        return gaussian_blur(in.uv, vec2<f32>(1.0, 0.0), in.parameters.x);
+    }
+    return gaussian_blur(in.uv, vec2<f32>(0.0, 1.0), in.parameters.x);
+}
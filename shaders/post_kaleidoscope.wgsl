@@ -0,0 +1,10 @@
+// Kaleidoscope: x segments, y rotation in degrees.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let point = in.uv - vec2<f32>(0.5);
+    let segment = 6.28318531 / max(in.parameters.x, 1.0);
+    var angle = atan2(point.y, point.x) + in.parameters.y * 0.01745329;
+    angle = angle - segment * floor(angle / segment);
+    angle = min(angle, segment - angle);
+    return sample_input(vec2<f32>(0.5) + vec2<f32>(cos(angle), sin(angle)) * length(point));
+}
@@ -0,0 +1,9 @@
+// Kawase blur: x offset in pixels, growing by one every stage.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let offset = input_texel_size() * (f32(stage_info.stage) + 0.5) * in.parameters.x;
+    return (sample_input(in.uv + offset)
+        + sample_input(in.uv - offset)
+        + sample_input(in.uv + vec2<f32>(offset.x, -offset.y))
+        + sample_input(in.uv + vec2<f32>(-offset.x, offset.y))) * 0.25;
+}
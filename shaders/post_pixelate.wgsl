@@ -0,0 +1,8 @@
+// Pixelate: x block size in pixels.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let dimensions = vec2<f32>(textureDimensions(input_texture));
+    let size = max(in.parameters.x, 1.0);
+    let block = (floor(in.uv * dimensions / size) + vec2<f32>(0.5)) * size / dimensions;
+    return sample_input(block);
+}
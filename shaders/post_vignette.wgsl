@@ -0,0 +1,9 @@
+// Vignette: x intensity, y smoothness.
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let color = sample_input(in.uv);
+    let distance = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let smoothness = clamp(in.parameters.y, 0.01, 1.0);
+    let factor = 1.0 - in.parameters.x * smoothStep(1.0 - smoothness, 1.0, distance);
+    return vec4<f32>(color.rgb * factor, color.a);
+}
@@ -8,11 +8,23 @@ struct VertexOutput {
     [[location(1)]] uv: vec2<f32>;
 };
 
+[[block]]
+struct Tonemapping {
+    operator: u32;
+    exposure: f32;
+};
+
+let TONEMAP_NONE: u32 = 0u;
+let TONEMAP_REINHARD: u32 = 1u;
+let TONEMAP_ACES: u32 = 2u;
+
 
 [[group(0), binding(0)]]
 var texture_input : texture_2d<f32>;
 [[group(0), binding(1)]]
 var sampler_input : sampler;
+[[group(1), binding(0)]]
+var<uniform> tonemapping: Tonemapping;
 
 
 [[stage(vertex)]]
@@ -25,9 +37,30 @@ fn main(
     return out;
 }
 
+//Narkowicz 2015, "ACES Filmic Tone Mapping Curve"
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+fn tonemap(color: vec3<f32>) -> vec3<f32> {
+    let exposed = max(color * tonemapping.exposure, vec3<f32>(0.0));
+    if (tonemapping.operator == TONEMAP_REINHARD) {
+        return exposed / (exposed + vec3<f32>(1.0));
+    }
+    if (tonemapping.operator == TONEMAP_ACES) {
+        return aces(exposed);
+    }
+    return exposed;
+}
+
 
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     var tex = textureSample(texture_input, sampler_input, in.uv);
-    return vec4<f32>(tex.r, tex.g, tex.b, 1.0) ;
+    return vec4<f32>(tonemap(tex.rgb), 1.0) ;
 }
@@ -0,0 +1,105 @@
+#version 450
+// Wraps a Shadertoy image shader, included with `#include <image>`, into a render shader
+#include <cubensis>
+
+#ifdef VERTEX
+layout(set = 3, binding = 0) uniform MeshTransform {
+    mat4 model;
+    mat4 normal;
+} mesh_transform;
+
+layout(location = 0) in vec3 position;
+
+void main() {
+    gl_Position = mesh_transform.model * vec4(position, 1.0);
+}
+#endif
+
+#ifdef FRAGMENT
+// Scene and audio textures have a single mip level, so they are sampled at level 0 explicitly,
+// which matches `texture` without requiring uniform control flow
+
+// iChannel0 is the audio input laid out like Shadertoy's, 512 texels wide:
+// the spectrum in the first row, the waveform mapped to [0, 1] in the second
+vec4 shadertoy_audio(vec2 uv) {
+    float value = textureLod(sampler1D(spectrum_texture, audio_sampler), uv.x, 0.0).r;
+    if (uv.y >= 0.5) {
+        value = 0.5 + 0.5 * textureLod(sampler1D(wave_texture, audio_sampler), uv.x, 0.0).r;
+    }
+    return vec4(value, value, value, 1.0);
+}
+
+// iChannel1 to iChannel3 are texture_1 to texture_3, flipped to Shadertoy's bottom left origin
+vec4 shadertoy_texture_lod(int channel, vec2 uv, float lod) {
+    vec2 flipped = vec2(uv.x, 1.0 - uv.y);
+    if (channel == 1) {
+        return textureLod(sampler2D(texture_1, texture_sampler), flipped, lod);
+    } else if (channel == 2) {
+        return textureLod(sampler2D(texture_2, texture_sampler), flipped, lod);
+    } else if (channel == 3) {
+        return textureLod(sampler2D(texture_3, texture_sampler), flipped, lod);
+    }
+    return shadertoy_audio(uv);
+}
+
+vec4 shadertoy_texture(int channel, vec2 uv) {
+    return shadertoy_texture_lod(channel, uv, 0.0);
+}
+
+vec4 shadertoy_texture(int channel, vec2 uv, float bias) {
+    return shadertoy_texture_lod(channel, uv, 0.0);
+}
+
+ivec2 shadertoy_texture_size(int channel, int lod) {
+    if (channel == 1) {
+        return textureSize(sampler2D(texture_1, texture_sampler), lod);
+    } else if (channel == 2) {
+        return textureSize(sampler2D(texture_2, texture_sampler), lod);
+    } else if (channel == 3) {
+        return textureSize(sampler2D(texture_3, texture_sampler), lod);
+    }
+    return ivec2(512, 2);
+}
+
+vec4 shadertoy_texel_fetch(int channel, ivec2 texel, int lod) {
+    ivec2 size = shadertoy_texture_size(channel, lod);
+    ivec2 flipped = ivec2(texel.x, size.y - 1 - texel.y);
+    if (channel == 1) {
+        return texelFetch(sampler2D(texture_1, texture_sampler), flipped, lod);
+    } else if (channel == 2) {
+        return texelFetch(sampler2D(texture_2, texture_sampler), flipped, lod);
+    } else if (channel == 3) {
+        return texelFetch(sampler2D(texture_3, texture_sampler), flipped, lod);
+    }
+    return shadertoy_audio((vec2(texel) + 0.5) / vec2(size));
+}
+
+#define iTime time_info.time_seconds
+#define iTimeDelta time_info.frame_time_seconds
+#define iFrame int(time_info.frame_index)
+#define iResolution vec3(view_info.resolution, 1.0)
+#define iMouse view_info.mouse
+// Channels are numbers rather than samplers, the texture functions are replaced by name with wrappers taking them
+#define iChannel0 0
+#define iChannel1 1
+#define iChannel2 2
+#define iChannel3 3
+#define texture shadertoy_texture
+#define textureLod shadertoy_texture_lod
+#define texelFetch shadertoy_texel_fetch
+#define textureSize shadertoy_texture_size
+#define HW_PERFORMANCE 1
+
+#include <image>
+
+layout(location = 0) out vec4 shadertoy_color;
+
+// Shadertoy's fragCoord is in pixels of the whole image with the origin at the bottom left
+void main() {
+    vec2 frag_coord = gl_FragCoord.xy + view_info.region_offset;
+    frag_coord.y = view_info.resolution.y - frag_coord.y;
+    vec4 color = vec4(0.0, 0.0, 0.0, 1.0);
+    mainImage(color, frag_coord);
+    shadertoy_color = vec4(color.rgb, 1.0);
+}
+#endif
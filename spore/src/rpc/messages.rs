@@ -1,7 +1,28 @@
 #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
 pub enum RpcRequest {
-    SetProject { project_name: String },
+    SetProject {
+        project_name: String,
+    },
+    Screenshot(hyphae::events::CaptureOptions),
+    ToggleRecording(hyphae::events::CaptureOptions),
+    SetMeshTransform {
+        mesh: String,
+        transform: hyphae::scene::transform::Transform,
+    },
 }
+///Pushed to the connected client whenever the state it describes changes, without a request
+#[derive(Clone, serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+pub enum RpcNotification {
+    ///Latest build error of every shader that currently fails to build, empty once they all build
+    ShaderDiagnostics(Vec<hyphae::diagnostics::ShaderDiagnostic>),
+}
+
+impl RpcNotification {
+    pub(crate) fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
 #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
 #[repr(u32)]
 pub enum RpcSeverity {
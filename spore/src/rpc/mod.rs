@@ -1,17 +1,22 @@
-use crate::rpc::messages::{RpcRequest, RpcResponse, RpcSeverity};
+use crate::rpc::messages::{RpcNotification, RpcRequest, RpcResponse, RpcSeverity};
 use hyphae::configuration::Configuration;
 use hyphae::events::CubensisEvent;
 use hyphae::plugins::CubensisRendererPlugin;
 use std::net::TcpStream;
+use std::sync::mpsc::{Receiver, Sender};
 use tungstenite::{accept, Error, Message, Result};
 use winit::event_loop::EventLoopProxy;
 pub mod messages;
 
+///How often a connection checks for notifications while waiting for requests
+const NOTIFICATION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
 #[derive(Debug)]
 pub struct RpcServer {
     configuration: Configuration,
     event_proxy: winit::event_loop::EventLoopProxy<CubensisEvent>,
     thread_handle: Option<std::thread::JoinHandle<()>>,
+    notification_sender: Option<Sender<RpcNotification>>,
 }
 
 impl CubensisRendererPlugin for RpcServer {
@@ -25,18 +30,31 @@ impl CubensisRendererPlugin for RpcServer {
             configuration,
             event_proxy,
             thread_handle,
+            notification_sender: None,
         }
     }
 
-    fn handle_event(&mut self, _: &CubensisEvent) {}
+    fn handle_event(&mut self, event: &CubensisEvent) {
+        if let (CubensisEvent::ShaderDiagnostics(diagnostics), Some(notification_sender)) =
+            (event, &self.notification_sender)
+        {
+            notification_sender
+                .send(RpcNotification::ShaderDiagnostics(diagnostics.clone()))
+                .ok();
+        }
+    }
 
     fn start(&mut self) {
         let address = self.configuration.network.get_address();
         if self.thread_handle.is_none() {
             log::debug!("Starting RPC websocket server");
             let event_proxy = self.event_proxy.clone();
+            let (notification_sender, notifications) = std::sync::mpsc::channel();
+            self.notification_sender = Some(notification_sender);
             self.thread_handle = Some(std::thread::spawn(move || {
                 log::debug!("Creating RPC thread");
+                //latest shader diagnostics, also sent to clients when they connect
+                let mut shader_diagnostics = RpcNotification::ShaderDiagnostics(Vec::new());
                 let listener =
                     std::net::TcpListener::bind(&*address).expect("Failed to bind to RPC address");
                 log::debug!("RPC Server listening on {}", address);
@@ -46,7 +64,13 @@ impl CubensisRendererPlugin for RpcServer {
                         .expect("connected streams should have a peer address");
                     log::info!("Connection from: {}", peer);
                     let mut websocket = accept(stream).unwrap();
-                    if let Err(e) = Self::handle_connection(peer, &mut websocket, &event_proxy) {
+                    if let Err(e) = Self::handle_connection(
+                        peer,
+                        &mut websocket,
+                        &event_proxy,
+                        &notifications,
+                        &mut shader_diagnostics,
+                    ) {
                         log::warn!("RPC error encountered");
                         match e {
                             Error::ConnectionClosed | Error::Io(_) => {
@@ -62,6 +86,7 @@ impl CubensisRendererPlugin for RpcServer {
 
     fn shutdown(&mut self) {
         self.thread_handle = None;
+        self.notification_sender = None;
     }
 }
 
@@ -70,10 +95,37 @@ impl RpcServer {
         peer: std::net::SocketAddr,
         websocket: &mut tungstenite::WebSocket<TcpStream>,
         event_proxy: &EventLoopProxy<CubensisEvent>,
+        notifications: &Receiver<RpcNotification>,
+        shader_diagnostics: &mut RpcNotification,
     ) -> Result<()> {
         log::info!("New WebSocket connection: {}", peer);
+        //wakes up regularly to push notifications between requests
+        websocket
+            .get_ref()
+            .set_read_timeout(Some(NOTIFICATION_POLL_INTERVAL))?;
+        Self::receive_notifications(notifications, shader_diagnostics);
+        if *shader_diagnostics != RpcNotification::ShaderDiagnostics(Vec::new()) {
+            websocket.write_message(Message::Text(shader_diagnostics.serialize().unwrap()))?;
+        }
         loop {
-            let msg = websocket.read_message()?;
+            let msg = match websocket.read_message() {
+                Ok(msg) => msg,
+                Err(Error::Io(error))
+                    if matches!(
+                        error.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    if Self::receive_notifications(notifications, shader_diagnostics) {
+                        log::debug!("Pushing shader diagnostics to RPC client");
+                        websocket.write_message(Message::Text(
+                            shader_diagnostics.serialize().unwrap(),
+                        ))?;
+                    }
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
             match msg {
                 Message::Text(ref text) => {
                     let request: Option<RpcRequest> = serde_json::from_str(text.as_str()).ok();
@@ -97,6 +149,19 @@ impl RpcServer {
             }
         }
     }
+    ///Keeps the latest of the queued notifications, returns true if there were any
+    fn receive_notifications(
+        notifications: &Receiver<RpcNotification>,
+        shader_diagnostics: &mut RpcNotification,
+    ) -> bool {
+        let mut received = false;
+        for notification in notifications.try_iter() {
+            *shader_diagnostics = notification;
+            received = true;
+        }
+        received
+    }
+
     fn handle_command(
         event_proxy: &EventLoopProxy<CubensisEvent>,
         sender: &mut tungstenite::WebSocket<TcpStream>,
@@ -104,19 +169,38 @@ impl RpcServer {
     ) -> Result<()> {
         log::debug!("Received RPC request: {:?}", command);
 
-        match command {
-            RpcRequest::SetProject { project_name } => {
-                event_proxy
-                    .send_event(CubensisEvent::SceneChange(project_name))
-                    .unwrap();
-                let response = RpcResponse::success(
-                    Some("Successfully loaded scene".to_string()),
-                    Some(RpcSeverity::Info),
-                );
-                let response = response.serialize().unwrap();
-                sender.write_message(Message::Text(response))?;
+        let (event, message, severity) = match command {
+            RpcRequest::SetProject { project_name } => (
+                CubensisEvent::SceneChange(project_name),
+                Some("Successfully loaded scene".to_string()),
+                Some(RpcSeverity::Info),
+            ),
+            RpcRequest::Screenshot(options) => (
+                CubensisEvent::Screenshot(options),
+                Some("Screenshot will be saved to the scene folder".to_string()),
+                Some(RpcSeverity::Info),
+            ),
+            RpcRequest::ToggleRecording(options) => (
+                CubensisEvent::ToggleRecording(options),
+                Some("Toggled recording".to_string()),
+                Some(RpcSeverity::Info),
+            ),
+            RpcRequest::SetMeshTransform { mesh, transform } => (
+                CubensisEvent::SetMeshTransform { mesh, transform },
+                None,
+                None,
+            ),
+        };
+        //the event loop closes while the application shuts down
+        let response = match event_proxy.send_event(event) {
+            Ok(_) => RpcResponse::success(message, severity),
+            Err(error) => {
+                log::warn!("Failed to forward RPC request: {}", error);
+                RpcResponse::error(format!("Failed to forward request: {}", error), None)
             }
-        }
+        };
+        let response = response.serialize().unwrap();
+        sender.write_message(Message::Text(response))?;
         Ok(())
     }
 }
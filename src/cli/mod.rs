@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+
+pub enum Command {
+    Interactive,
+    Render(RenderArguments),
+    Export(ExportArguments),
+    Tiled(TiledArguments),
+    Prelude(PreludeArguments),
+}
+
+pub struct RenderArguments {
+    pub width: u32,
+    pub height: u32,
+    pub frames: u32,
+    ///WAV file, the only format that can be decoded; silent if missing
+    pub audio: Option<PathBuf>,
+    pub output: PathBuf,
+}
+
+impl Default for RenderArguments {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            frames: 1,
+            audio: None,
+            output: PathBuf::from("cubensis.png"),
+        }
+    }
+}
+
+pub struct ExportArguments {
+    pub width: u32,
+    pub height: u32,
+    pub frames_per_second: u32,
+    ///WAV file, the only format that can be decoded
+    pub audio: PathBuf,
+    ///Defaults to the length of the audio file
+    pub duration: Option<f64>,
+    pub output: PathBuf,
+    pub encoder: Option<Vec<String>>,
+}
+
+impl Default for ExportArguments {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            frames_per_second: 60,
+            audio: PathBuf::new(),
+            duration: None,
+            output: PathBuf::from("cubensis_export"),
+            encoder: None,
+        }
+    }
+}
+
+pub struct TiledArguments {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    ///Seconds into the scene (and audio file) the still is taken at
+    pub time: f64,
+    ///WAV file, the only format that can be decoded; silent if missing
+    pub audio: Option<PathBuf>,
+    pub settle_frames: u32,
+    pub output: PathBuf,
+}
+
+impl Default for TiledArguments {
+    fn default() -> Self {
+        Self {
+            width: 16384,
+            height: 16384,
+            tile_size: 4096,
+            time: 0.0,
+            audio: None,
+            settle_frames: 1,
+            output: PathBuf::from("cubensis_tiled.png"),
+        }
+    }
+}
+
+///Writes the generated resource declarations, for editors and language servers
+#[derive(Default)]
+pub struct PreludeArguments {
+    ///Printed to stdout if missing
+    pub output: Option<PathBuf>,
+    ///GLSL declarations instead of WGSL, with `--language glsl`
+    pub glsl: bool,
+}
+
+impl Command {
+    pub fn from_args() -> anyhow::Result<Self> {
+        let mut args = std::env::args().skip(1);
+        match args.next().as_deref() {
+            None => Ok(Command::Interactive),
+            Some("render") => {
+                let mut arguments = RenderArguments::default();
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::Error::msg(format!("Missing value for {}", flag)))?;
+                    match flag.as_str() {
+                        "--width" => arguments.width = value.parse()?,
+                        "--height" => arguments.height = value.parse()?,
+                        "--frames" => arguments.frames = value.parse()?,
+                        "--audio" => arguments.audio = Some(PathBuf::from(value)),
+                        "--output" => arguments.output = PathBuf::from(value),
+                        _ => return Err(anyhow::Error::msg(format!("Unknown flag {}", flag))),
+                    }
+                }
+                Ok(Command::Render(arguments))
+            }
+            Some("export") => {
+                let mut arguments = ExportArguments::default();
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::Error::msg(format!("Missing value for {}", flag)))?;
+                    match flag.as_str() {
+                        "--width" => arguments.width = value.parse()?,
+                        "--height" => arguments.height = value.parse()?,
+                        "--fps" => {
+                            arguments.frames_per_second = value.parse()?;
+                            if arguments.frames_per_second == 0 {
+                                return Err(anyhow::Error::msg("--fps must be at least 1"));
+                            }
+                        }
+                        "--audio" => arguments.audio = PathBuf::from(value),
+                        "--duration" => arguments.duration = Some(value.parse()?),
+                        "--output" => arguments.output = PathBuf::from(value),
+                        "--encoder" => arguments.encoder = Some(split_command(&value)?),
+                        _ => return Err(anyhow::Error::msg(format!("Unknown flag {}", flag))),
+                    }
+                }
+                if arguments.audio.as_os_str().is_empty() {
+                    return Err(anyhow::Error::msg("Export requires an --audio WAV file"));
+                }
+                Ok(Command::Export(arguments))
+            }
+            Some("tiled") => {
+                let mut arguments = TiledArguments::default();
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::Error::msg(format!("Missing value for {}", flag)))?;
+                    match flag.as_str() {
+                        "--width" => arguments.width = value.parse()?,
+                        "--height" => arguments.height = value.parse()?,
+                        "--tile-size" => arguments.tile_size = value.parse()?,
+                        "--time" => arguments.time = value.parse()?,
+                        "--audio" => arguments.audio = Some(PathBuf::from(value)),
+                        "--settle-frames" => arguments.settle_frames = value.parse()?,
+                        "--output" => arguments.output = PathBuf::from(value),
+                        _ => return Err(anyhow::Error::msg(format!("Unknown flag {}", flag))),
+                    }
+                }
+                Ok(Command::Tiled(arguments))
+            }
+            Some("prelude") => {
+                let mut arguments = PreludeArguments::default();
+                while let Some(flag) = args.next() {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow::Error::msg(format!("Missing value for {}", flag)))?;
+                    match flag.as_str() {
+                        "--output" => arguments.output = Some(PathBuf::from(value)),
+                        "--language" => {
+                            arguments.glsl = match value.as_str() {
+                                "wgsl" => false,
+                                "glsl" => true,
+                                _ => {
+                                    return Err(anyhow::Error::msg(format!(
+                                        "Unknown prelude language {}",
+                                        value
+                                    )))
+                                }
+                            }
+                        }
+                        _ => return Err(anyhow::Error::msg(format!("Unknown flag {}", flag))),
+                    }
+                }
+                Ok(Command::Prelude(arguments))
+            }
+            Some(command) => Err(anyhow::Error::msg(format!("Unknown command {}", command))),
+        }
+    }
+}
+
+///Splits an encoder command into its arguments like a shell would, honouring quotes and backslashes
+fn split_command(command: &str) -> anyhow::Result<Vec<String>> {
+    let mut arguments = Vec::new();
+    let mut argument: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut characters = command.chars();
+    while let Some(character) = characters.next() {
+        match (quote, character) {
+            (Some(open), _) if character == open => quote = None,
+            (None, '\'' | '"') => {
+                quote = Some(character);
+                argument.get_or_insert_with(String::new);
+            }
+            (None | Some('"'), '\\') => {
+                let escaped = characters
+                    .next()
+                    .ok_or_else(|| anyhow::Error::msg("Trailing backslash in --encoder"))?;
+                argument.get_or_insert_with(String::new).push(escaped);
+            }
+            (None, _) if character.is_whitespace() => arguments.extend(argument.take()),
+            _ => argument.get_or_insert_with(String::new).push(character),
+        }
+    }
+    if quote.is_some() {
+        return Err(anyhow::Error::msg("Unterminated quote in --encoder"));
+    }
+    arguments.extend(argument);
+    Ok(arguments)
+}
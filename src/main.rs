@@ -1,16 +1,97 @@
+use crate::cli::{Command, ExportArguments, PreludeArguments, RenderArguments, TiledArguments};
 use crate::plugins::PluginCollection;
 use hyphae::configuration::Configuration;
 use psilocybin::gui::GuiApp;
 use psilocybin::resources::ResourceCollection;
+use psilocyn::export::{ExportSettings, ExportTarget, OfflineExporter};
+use psilocyn::headless::HeadlessRenderer;
 use psilocyn::renderer::Renderer;
+use psilocyn::resources::CubensisResourceCollection;
+use psilocyn::tiled::{TiledExporter, TiledSettings};
+use substrate::file_stream::AudioFileStream;
 
+mod cli;
 mod plugins;
 
-const HISTORY_DEPTH: usize = 1;
-
-fn main() -> ! {
+fn main() -> anyhow::Result<()> {
     env_logger::init();
     Configuration::create_if_missing().unwrap();
     let configuration = Configuration::load();
-    Renderer::<ResourceCollection, GuiApp, PluginCollection, HISTORY_DEPTH>::run(configuration);
+    match Command::from_args()? {
+        Command::Interactive => {
+            Renderer::<ResourceCollection, GuiApp, PluginCollection>::run(configuration)
+        }
+        Command::Render(arguments) => render(configuration, arguments),
+        Command::Export(arguments) => export(configuration, arguments),
+        Command::Tiled(arguments) => tiled(configuration, arguments),
+        Command::Prelude(arguments) => prelude(arguments),
+    }
+}
+
+fn render(mut configuration: Configuration, arguments: RenderArguments) -> anyhow::Result<()> {
+    log::info!("Rendering {} headless frame(s)", arguments.frames);
+    if arguments.audio.is_some() {
+        configuration.audio.set_input_file(arguments.audio.clone());
+    }
+    let size = winit::dpi::PhysicalSize::new(arguments.width, arguments.height);
+    let mut renderer = HeadlessRenderer::<ResourceCollection>::new(configuration, size)?;
+    let time_delta = std::time::Duration::from_secs_f64(1.0 / 60.0);
+    for _ in 0..arguments.frames.max(1) {
+        renderer.render_frame(time_delta);
+    }
+    renderer.read_frame()?.save(&arguments.output)?;
+    log::info!("Saved frame to {:?}", arguments.output);
+    Ok(())
+}
+
+fn export(mut configuration: Configuration, arguments: ExportArguments) -> anyhow::Result<()> {
+    let duration = match arguments.duration {
+        Some(seconds) => seconds,
+        None => AudioFileStream::duration(&arguments.audio)?.as_secs_f64(),
+    };
+    let frame_count = (duration * arguments.frames_per_second as f64).ceil() as u64;
+    configuration
+        .audio
+        .set_input_file(Some(arguments.audio.clone()));
+    let target = match arguments.encoder {
+        Some(command) => ExportTarget::Encoder(command),
+        None => ExportTarget::ImageSequence(arguments.output),
+    };
+    let settings = ExportSettings {
+        size: winit::dpi::PhysicalSize::new(arguments.width, arguments.height),
+        frames_per_second: arguments.frames_per_second,
+        frame_count,
+        target,
+    };
+    OfflineExporter::<ResourceCollection>::new(configuration, settings)?.run()
+}
+
+fn tiled(mut configuration: Configuration, arguments: TiledArguments) -> anyhow::Result<()> {
+    if arguments.audio.is_some() {
+        configuration.audio.set_input_file(arguments.audio.clone());
+    }
+    let settings = TiledSettings {
+        size: winit::dpi::PhysicalSize::new(arguments.width, arguments.height),
+        tile_size: arguments.tile_size,
+        time: std::time::Duration::from_secs_f64(arguments.time),
+        settle_frames: arguments.settle_frames,
+        output: arguments.output,
+    };
+    TiledExporter::<ResourceCollection>::new(configuration, settings)?.run()
+}
+
+fn prelude(arguments: PreludeArguments) -> anyhow::Result<()> {
+    let prelude = if arguments.glsl {
+        ResourceCollection::glsl_prelude()
+    } else {
+        ResourceCollection::wgsl_prelude()
+    };
+    match arguments.output {
+        Some(output) => {
+            std::fs::write(&output, prelude)?;
+            log::info!("Saved shader prelude to {:?}", output);
+        }
+        None => print!("{}", prelude),
+    }
+    Ok(())
 }
@@ -22,8 +22,10 @@ impl CubensisPluginCollection for PluginCollection {
         Self { rpc, file_watcher }
     }
 
-    fn handle_event(&mut self, _event: &CubensisEvent) {
+    fn handle_event(&mut self, event: &CubensisEvent) {
         log::trace!("Handling event in plugin collection");
+        self.rpc.handle_event(event);
+        self.file_watcher.handle_event(event);
     }
 
     fn start_all(&mut self) {
@@ -0,0 +1,39 @@
+use crate::stream_info::AudioStreamInfo;
+
+///Anything that produces wave and spectrum data for the audio resource
+pub trait AudioAnalysisSource {
+    fn get_wave_and_spectrum_data(&mut self) -> (&Vec<f32>, &Vec<f32>);
+    fn get_stream_info(&self) -> AudioStreamInfo;
+    ///Moves the playhead of sources which aren't driven by a real-time stream
+    fn advance(&mut self, _time_delta: std::time::Duration) {}
+}
+
+pub(crate) fn compute_spectrum(data: &[f32], sample_rate: u32) -> Vec<f32> {
+    let hamming_window = spectrum_analyzer::windows::hamming_window(data);
+    let spectrum_hamming_window = spectrum_analyzer::samples_fft_to_spectrum(
+        &hamming_window,
+        sample_rate,
+        spectrum_analyzer::FrequencyLimit::Range(10.0, 8000.0),
+        Some(&scaling_function), //Scales to amplitudes
+    )
+    .unwrap();
+    spectrum_hamming_window
+        .data()
+        .iter()
+        .map(|(_, fr_val)| fr_val.val())
+        .collect()
+}
+
+fn scaling_function(
+    frequency_magnitude: f32,
+    _stats: &spectrum_analyzer::scaling::SpectrumDataStats,
+) -> f32 {
+    debug_assert!(!frequency_magnitude.is_infinite());
+    debug_assert!(!frequency_magnitude.is_nan());
+    debug_assert!(frequency_magnitude >= 0.0);
+    if frequency_magnitude == 0.0 {
+        0.0
+    } else {
+        libm::log10f(frequency_magnitude) - 5.5
+    }
+}
@@ -0,0 +1,104 @@
+use crate::analysis::{compute_spectrum, AudioAnalysisSource};
+use crate::stream_info::AudioStreamInfo;
+use hyphae::configuration::Configuration;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+///Analyzes a decoded audio file at an explicitly advanced playhead rather than live input
+pub struct AudioFileStream {
+    path: PathBuf,
+    samples: Vec<f32>,
+    sample_rate: u32,
+    buffer_size: usize,
+    playhead: Duration,
+    wave_data: Vec<f32>,
+    spectrum_data: Vec<f32>,
+}
+
+impl AudioFileStream {
+    pub fn new(path: &Path, configuration: Configuration) -> anyhow::Result<Self> {
+        log::debug!("Loading audio file {:?}", path);
+        let (samples, sample_rate) = decode_wav(path)?;
+        let buffer_size = configuration.audio.get_buffer_size();
+        let mut stream = Self {
+            path: path.to_path_buf(),
+            samples,
+            sample_rate,
+            buffer_size,
+            playhead: Duration::ZERO,
+            wave_data: vec![0.0; buffer_size],
+            spectrum_data: vec![0.0; buffer_size],
+        };
+        stream.analyze();
+        Ok(stream)
+    }
+
+    ///Length of the audio file without decoding its samples
+    pub fn duration(path: &Path) -> anyhow::Result<Duration> {
+        let reader = hound::WavReader::open(path)?;
+        let sample_rate = reader.spec().sample_rate as f64;
+        Ok(Duration::from_secs_f64(reader.duration() as f64 / sample_rate))
+    }
+
+    pub fn get_playhead(&self) -> Duration {
+        self.playhead
+    }
+
+    pub fn seek(&mut self, playhead: Duration) {
+        self.playhead = playhead;
+        self.analyze();
+    }
+
+    ///Index of the sample at the playhead, computed with integer math so no drift accumulates
+    fn sample_offset(&self) -> usize {
+        (self.playhead.as_nanos() * self.sample_rate as u128 / 1_000_000_000) as usize
+    }
+
+    fn analyze(&mut self) {
+        log::trace!("Analyzing audio file at {:?}", self.playhead);
+        let end = self.sample_offset().min(self.samples.len());
+        let start = end.saturating_sub(self.buffer_size);
+        //the window ends at the playhead and is zero-padded before the file starts, like live input
+        let padding = self.buffer_size - (end - start);
+        self.wave_data.clear();
+        self.wave_data.resize(padding, 0.0);
+        self.wave_data.extend_from_slice(&self.samples[start..end]);
+        self.spectrum_data = compute_spectrum(&self.wave_data, self.sample_rate);
+    }
+}
+
+impl AudioAnalysisSource for AudioFileStream {
+    fn get_wave_and_spectrum_data(&mut self) -> (&Vec<f32>, &Vec<f32>) {
+        (&self.wave_data, &self.spectrum_data)
+    }
+
+    fn get_stream_info(&self) -> AudioStreamInfo {
+        AudioStreamInfo::new(self.path.to_string_lossy().to_string())
+    }
+
+    fn advance(&mut self, time_delta: Duration) {
+        self.seek(self.playhead + time_delta);
+    }
+}
+
+///Decodes a WAV file into mono samples
+fn decode_wav(path: &Path) -> anyhow::Result<(Vec<f32>, u32)> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1_i64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| s as f32 * scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    let channels = spec.channels.max(1) as usize;
+    let samples = interleaved
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    Ok((samples, spec.sample_rate))
+}
@@ -2,6 +2,9 @@ use crate::AudioStreamSource::{InputStream, WasapiLoopback};
 use cpal::traits::{DeviceTrait, HostTrait};
 use itertools::Itertools;
 
+pub mod analysis;
+pub mod file_stream;
+pub mod silent_stream;
 pub mod wave_stream;
 pub mod stream_info;
 
@@ -28,27 +31,22 @@ impl AudioStreamSource {
         streams
     }
 
-    pub fn default_stream() -> Self {
+    ///Fails on machines without a default input (or, on Windows, output) device
+    pub fn default_stream() -> anyhow::Result<Self> {
         let host = cpal::default_host();
         #[cfg(windows)]
         {
-            WasapiLoopback(
-                host.default_output_device()
-                    .unwrap()
-                    .name()
-                    .unwrap()
-                    .to_owned(),
-            )
+            let device = host
+                .default_output_device()
+                .ok_or_else(|| anyhow::Error::msg("No default audio output device"))?;
+            Ok(WasapiLoopback(device.name()?))
         }
         #[cfg(not(windows))]
         {
-            InputStream(
-                host.default_input_device()
-                    .unwrap()
-                    .name()
-                    .unwrap()
-                    .to_owned(),
-            )
+            let device = host
+                .default_input_device()
+                .ok_or_else(|| anyhow::Error::msg("No default audio input device"))?;
+            Ok(InputStream(device.name()?))
         }
     }
     pub fn name(&self) -> String {
@@ -0,0 +1,32 @@
+use crate::analysis::{compute_spectrum, AudioAnalysisSource};
+use crate::stream_info::AudioStreamInfo;
+use hyphae::configuration::Configuration;
+
+///Constant silence, for headless rendering without an audio file or machines without an input device
+pub struct SilentStream {
+    wave_data: Vec<f32>,
+    spectrum_data: Vec<f32>,
+}
+
+impl SilentStream {
+    pub fn new(configuration: Configuration) -> Self {
+        log::debug!("Creating silent audio stream");
+        let wave_data = vec![0.0; configuration.audio.get_buffer_size()];
+        //same spectrum length as live input analyzed at a common sample rate
+        let spectrum_data = compute_spectrum(&wave_data, 44100);
+        Self {
+            wave_data,
+            spectrum_data,
+        }
+    }
+}
+
+impl AudioAnalysisSource for SilentStream {
+    fn get_wave_and_spectrum_data(&mut self) -> (&Vec<f32>, &Vec<f32>) {
+        (&self.wave_data, &self.spectrum_data)
+    }
+
+    fn get_stream_info(&self) -> AudioStreamInfo {
+        AudioStreamInfo::new("Silence".to_string())
+    }
+}
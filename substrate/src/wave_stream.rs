@@ -1,3 +1,4 @@
+use crate::analysis::{compute_spectrum, AudioAnalysisSource};
 use crate::stream_info::AudioStreamInfo;
 use crate::AudioStreamSource;
 use cpal::traits::{DeviceTrait, StreamTrait};
@@ -36,19 +37,7 @@ impl WaveStream {
                 wave_buffer.push(*float);
             }
             let data: Vec<f32> = wave_buffer.iter().map(|f| *f).collect();
-            let hamming_window = spectrum_analyzer::windows::hamming_window(&data[..]);
-            let spectrum_hamming_window = spectrum_analyzer::samples_fft_to_spectrum(
-                &hamming_window,
-                sample_rate,
-                spectrum_analyzer::FrequencyLimit::Range(10.0, 8000.0),
-                Some(&scaling_function), //Scales to amplitudes
-            )
-            .unwrap();
-            let frequency_values: Vec<_> = spectrum_hamming_window
-                .data()
-                .iter()
-                .map(|(_, fr_val)| fr_val.val())
-                .collect();
+            let frequency_values = compute_spectrum(&data, sample_rate);
             wave_data_sender.update(data).unwrap();
             frequency_data_sender.update(frequency_values).unwrap();
         };
@@ -73,24 +62,6 @@ impl WaveStream {
         }
     }
 
-    pub fn get_stream_info(&self) -> AudioStreamInfo {
-        AudioStreamInfo::new(self.audio_device.name().unwrap_or_default())
-    }
-
-    pub fn get_wave_and_spectrum_data(&mut self) -> (&Vec<f32>, &Vec<f32>) {
-        if self.audio_stream.borrow().is_none() {
-            let last_checked = std::time::Instant::now() - self.audio_stream_created;
-            if last_checked.as_secs_f32() > 5.0 {
-                self.audio_stream_created = std::time::Instant::now();
-                self.try_recreate_stream();
-            }
-        }
-        (
-            self.wave_data_receiver.latest(),
-            self.frequency_data_receiver.latest(),
-        )
-    }
-
     fn try_recreate_stream(&mut self) {
         let mut wave_buffer: dasp_ring_buffer::Fixed<Vec<f32>> =
             dasp_ring_buffer::Fixed::from(vec![0.0; self.buffer_size]);
@@ -103,19 +74,7 @@ impl WaveStream {
                 wave_buffer.push(*float);
             }
             let data: Vec<f32> = wave_buffer.iter().map(|f| *f).collect();
-            let hamming_window = spectrum_analyzer::windows::hamming_window(&data[..]);
-            let spectrum_hamming_window = spectrum_analyzer::samples_fft_to_spectrum(
-                &hamming_window,
-                sample_rate,
-                spectrum_analyzer::FrequencyLimit::Range(10.0, 8000.0),
-                Some(&scaling_function), //Scales to amplitudes
-            )
-            .unwrap();
-            let frequency_values: Vec<_> = spectrum_hamming_window
-                .data()
-                .iter()
-                .map(|(_, fr_val)| fr_val.val())
-                .collect();
+            let frequency_values = compute_spectrum(&data, sample_rate);
             frequency_data_sender.update(frequency_values).unwrap();
         };
         self.frequency_data_receiver = frequency_data_receiver;
@@ -132,17 +91,23 @@ impl WaveStream {
     }
 }
 
-fn scaling_function(
-    frequency_magnitude: f32,
-    _stats: &spectrum_analyzer::scaling::SpectrumDataStats,
-) -> f32 {
-    debug_assert!(!frequency_magnitude.is_infinite());
-    debug_assert!(!frequency_magnitude.is_nan());
-    debug_assert!(frequency_magnitude >= 0.0);
-    if frequency_magnitude == 0.0 {
-        0.0
-    } else {
-        libm::log10f(frequency_magnitude) - 5.5
+impl AudioAnalysisSource for WaveStream {
+    fn get_stream_info(&self) -> AudioStreamInfo {
+        AudioStreamInfo::new(self.audio_device.name().unwrap_or_default())
+    }
+
+    fn get_wave_and_spectrum_data(&mut self) -> (&Vec<f32>, &Vec<f32>) {
+        if self.audio_stream.borrow().is_none() {
+            let last_checked = std::time::Instant::now() - self.audio_stream_created;
+            if last_checked.as_secs_f32() > 5.0 {
+                self.audio_stream_created = std::time::Instant::now();
+                self.try_recreate_stream();
+            }
+        }
+        (
+            self.wave_data_receiver.latest(),
+            self.frequency_data_receiver.latest(),
+        )
     }
 }
 